@@ -0,0 +1,75 @@
+//! Compares evaluating a solution against the flat literal arena of `Formula`
+//! with the previous layout, where each clause was its own `HashMap`
+
+use std::collections::HashMap;
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use genetic_sat::sat::{Formula, Literal, Solution};
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+const NUMBER_OF_VARIABLES: usize = 25_000;
+const NUMBER_OF_CLAUSES: usize = 100_000;
+const CLAUSE_LENGTH: usize = 3;
+
+struct HashMapFormula {
+    clauses: Vec<HashMap<usize, bool>>,
+}
+
+impl HashMapFormula {
+    fn evaluate(&self, solution: &[bool]) -> usize {
+        self.clauses
+            .iter()
+            .filter(|clause| {
+                clause
+                    .iter()
+                    .any(|(variable, value)| solution[*variable] == *value)
+            })
+            .count()
+    }
+}
+
+fn random_clauses(rng: &mut StdRng) -> Vec<Vec<(usize, bool)>> {
+    (0..NUMBER_OF_CLAUSES)
+        .map(|_| {
+            (0..CLAUSE_LENGTH)
+                .map(|_| (rng.random_range(0..NUMBER_OF_VARIABLES), rng.random()))
+                .collect()
+        })
+        .collect()
+}
+
+fn evaluate_solution(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let clauses = random_clauses(&mut rng);
+    let solution = Solution::random(NUMBER_OF_VARIABLES, &mut rng);
+    let solution_values: Vec<bool> = solution.iter().collect();
+
+    let hash_map_formula = HashMapFormula {
+        clauses: clauses
+            .iter()
+            .map(|clause| clause.iter().copied().collect())
+            .collect(),
+    };
+    let mut formula = Formula::new(NUMBER_OF_VARIABLES);
+    for clause in &clauses {
+        let literals: Vec<Literal> = clause
+            .iter()
+            .map(|&(variable, value)| Literal::new(variable, value))
+            .collect();
+        formula.push_clause(&literals);
+    }
+
+    let mut group = c.benchmark_group("evaluate 100k clauses");
+    group.bench_function("hash map per clause", |b| {
+        b.iter(|| hash_map_formula.evaluate(black_box(&solution_values)));
+    });
+    group.bench_function("flat literal arena", |b| {
+        b.iter(|| black_box(&solution).evaluate(black_box(&formula)));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, evaluate_solution);
+criterion_main!(benches);
@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::sat::Formula;
+
+/// The structure of a formula, to know what the genetic algorithm is given before tuning it.
+/// The clauses are analysed as written, before any simplification of the parser.
+/// Ratios and fractions are 0 for formulas without variables or clauses
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FormulaAnalysis {
+    pub declared_variables: usize,
+    /// The variables occurring in at least one clause
+    pub used_variables: usize,
+    pub clauses: usize,
+    /// The number of clauses per declared variable
+    pub clause_variable_ratio: f64,
+    /// The number of clauses of each length
+    pub clause_lengths: BTreeMap<usize, usize>,
+    pub positive_literals: usize,
+    pub negative_literals: usize,
+    /// The used variables occurring with a single polarity
+    pub pure_literals: usize,
+    pub empty_clauses: usize,
+    pub unit_clauses: usize,
+    /// Clauses made of the same literals as a previous clause, in any order
+    pub duplicate_clauses: usize,
+    /// Clauses containing a literal and its negation
+    pub tautological_clauses: usize,
+    /// Literals repeated within a clause
+    pub duplicate_literals: usize,
+    /// The fraction of clauses with at most one positive literal
+    pub horn_fraction: f64,
+    /// The fraction of clauses with at most two literals
+    pub two_sat_fraction: f64,
+    /// How many clauses each declared variable occurs in
+    pub variable_occurrences: OccurrenceDistribution,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OccurrenceDistribution {
+    pub minimum: usize,
+    pub median: f64,
+    pub mean: f64,
+    pub maximum: usize,
+}
+
+impl FormulaAnalysis {
+    #[must_use]
+    pub fn new(formula: &Formula) -> Self {
+        let mut clause_lengths = BTreeMap::new();
+        let mut positive_occurrences = vec![0; formula.number_of_variables];
+        let mut negative_occurrences = vec![0; formula.number_of_variables];
+        let mut variable_occurrences = vec![0; formula.number_of_variables];
+        let mut distinct_clauses = HashSet::new();
+        let mut duplicate_clauses = 0;
+        let mut tautological_clauses = 0;
+        let mut duplicate_literals = 0;
+        let mut horn_clauses = 0;
+
+        for clause in formula.clauses() {
+            *clause_lengths.entry(clause.len()).or_insert(0) += 1;
+            for literal in clause {
+                if literal.value() {
+                    positive_occurrences[literal.variable()] += 1;
+                } else {
+                    negative_occurrences[literal.variable()] += 1;
+                }
+            }
+            if clause.iter().filter(|literal| literal.value()).count() <= 1 {
+                horn_clauses += 1;
+            }
+
+            let mut literals = clause.to_vec();
+            literals.sort_unstable();
+            literals.dedup();
+            duplicate_literals += clause.len() - literals.len();
+            if literals
+                .windows(2)
+                .any(|pair| pair[0].variable() == pair[1].variable())
+            {
+                tautological_clauses += 1;
+            }
+            let mut variables: Vec<usize> =
+                literals.iter().map(|literal| literal.variable()).collect();
+            variables.dedup();
+            for variable in variables {
+                variable_occurrences[variable] += 1;
+            }
+            if !distinct_clauses.insert(literals) {
+                duplicate_clauses += 1;
+            }
+        }
+
+        let clauses = formula.number_of_clauses();
+        let used_variables = variable_occurrences
+            .iter()
+            .filter(|&&occurrences| occurrences > 0)
+            .count();
+        let pure_literals = positive_occurrences
+            .iter()
+            .zip(&negative_occurrences)
+            .filter(|&(&positive, &negative)| (positive == 0) != (negative == 0))
+            .count();
+        let two_sat_clauses = clause_lengths.range(..=2).map(|(_, count)| count).sum();
+        let empty_clauses = clause_lengths.get(&0).copied().unwrap_or(0);
+        let unit_clauses = clause_lengths.get(&1).copied().unwrap_or(0);
+
+        FormulaAnalysis {
+            declared_variables: formula.number_of_variables,
+            used_variables,
+            clauses,
+            clause_variable_ratio: ratio(clauses, formula.number_of_variables),
+            clause_lengths,
+            positive_literals: positive_occurrences.iter().sum(),
+            negative_literals: negative_occurrences.iter().sum(),
+            pure_literals,
+            empty_clauses,
+            unit_clauses,
+            duplicate_clauses,
+            tautological_clauses,
+            duplicate_literals,
+            horn_fraction: ratio(horn_clauses, clauses),
+            two_sat_fraction: ratio(two_sat_clauses, clauses),
+            variable_occurrences: OccurrenceDistribution::new(variable_occurrences),
+        }
+    }
+}
+
+impl OccurrenceDistribution {
+    fn new(mut occurrences: Vec<usize>) -> Self {
+        occurrences.sort_unstable();
+        let Some((&minimum, &maximum)) = occurrences.first().zip(occurrences.last()) else {
+            return OccurrenceDistribution {
+                minimum: 0,
+                median: 0.,
+                mean: 0.,
+                maximum: 0,
+            };
+        };
+        let middle = occurrences.len() / 2;
+        let median = if occurrences.len().is_multiple_of(2) {
+            ratio(occurrences[middle - 1] + occurrences[middle], 2)
+        } else {
+            ratio(occurrences[middle], 1)
+        };
+
+        OccurrenceDistribution {
+            minimum,
+            median,
+            mean: ratio(occurrences.iter().sum(), occurrences.len()),
+            maximum,
+        }
+    }
+}
+
+/// `numerator / denominator`, or 0 when the denominator is 0
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        return 0.;
+    }
+    let as_f64 = |count: usize| {
+        f64::from(
+            u32::try_from(count).expect("The counts should be less than the maximum value of u32"),
+        )
+    };
+    as_f64(numerator) / as_f64(denominator)
+}
+
+/// Formats the analysis as a table of two columns, the name and value of each statistic
+impl fmt::Display for FormulaAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<(String, String)> = [
+            ("Declared variables", self.declared_variables.to_string()),
+            ("Used variables", self.used_variables.to_string()),
+            ("Clauses", self.clauses.to_string()),
+            (
+                "Clause/variable ratio",
+                format!("{:.3}", self.clause_variable_ratio),
+            ),
+            ("Positive literals", self.positive_literals.to_string()),
+            ("Negative literals", self.negative_literals.to_string()),
+            ("Pure literals", self.pure_literals.to_string()),
+            ("Empty clauses", self.empty_clauses.to_string()),
+            ("Unit clauses", self.unit_clauses.to_string()),
+            ("Duplicate clauses", self.duplicate_clauses.to_string()),
+            (
+                "Tautological clauses",
+                self.tautological_clauses.to_string(),
+            ),
+            ("Duplicate literals", self.duplicate_literals.to_string()),
+            ("Horn fraction", format!("{:.3}", self.horn_fraction)),
+            ("2-SAT fraction", format!("{:.3}", self.two_sat_fraction)),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .chain(
+            self.clause_lengths
+                .iter()
+                .map(|(length, count)| (format!("Clauses of length {length}"), count.to_string())),
+        )
+        .chain([
+            (
+                "Minimum occurrences of a variable".to_string(),
+                self.variable_occurrences.minimum.to_string(),
+            ),
+            (
+                "Median occurrences of a variable".to_string(),
+                self.variable_occurrences.median.to_string(),
+            ),
+            (
+                "Mean occurrences of a variable".to_string(),
+                format!("{:.3}", self.variable_occurrences.mean),
+            ),
+            (
+                "Maximum occurrences of a variable".to_string(),
+                self.variable_occurrences.maximum.to_string(),
+            ),
+        ])
+        .collect();
+
+        let name_width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, value) in rows {
+            writeln!(f, "{name:<name_width$}  {value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sat::Literal;
+
+    fn formula() -> Formula {
+        let mut formula = Formula::new(5);
+        formula.push_clause(&[Literal::new(0, true), Literal::new(1, false)]);
+        formula.push_clause(&[Literal::new(1, false), Literal::new(0, true)]);
+        formula.push_clause(&[Literal::new(2, true)]);
+        formula.push_clause(&[
+            Literal::new(0, true),
+            Literal::new(2, true),
+            Literal::new(3, false),
+        ]);
+        formula.push_clause(&[
+            Literal::new(1, true),
+            Literal::new(1, false),
+            Literal::new(3, false),
+            Literal::new(3, false),
+        ]);
+        formula
+    }
+
+    #[test]
+    fn new_should_count_the_structure_of_the_formula() {
+        let analysis = FormulaAnalysis::new(&formula());
+
+        assert_eq!(analysis.declared_variables, 5);
+        assert_eq!(analysis.used_variables, 4);
+        assert_eq!(analysis.clauses, 5);
+        assert!((analysis.clause_variable_ratio - 1.).abs() < f64::EPSILON);
+        assert_eq!(
+            analysis.clause_lengths,
+            BTreeMap::from([(1, 1), (2, 2), (3, 1), (4, 1)])
+        );
+        assert_eq!(analysis.positive_literals, 6);
+        assert_eq!(analysis.negative_literals, 6);
+        assert_eq!(analysis.pure_literals, 3);
+        assert_eq!(analysis.empty_clauses, 0);
+        assert_eq!(analysis.unit_clauses, 1);
+        assert_eq!(analysis.duplicate_clauses, 1);
+        assert_eq!(analysis.tautological_clauses, 1);
+        assert_eq!(analysis.duplicate_literals, 1);
+        assert!((analysis.horn_fraction - 0.8).abs() < f64::EPSILON);
+        assert!((analysis.two_sat_fraction - 0.6).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn new_should_describe_the_occurrences_of_every_declared_variable() {
+        let analysis = FormulaAnalysis::new(&formula());
+
+        // Variables 1 to 5 occur in 3, 3, 2, 2 and 0 clauses
+        assert_eq!(
+            analysis.variable_occurrences,
+            OccurrenceDistribution {
+                minimum: 0,
+                median: 2.,
+                mean: 2.,
+                maximum: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn an_empty_formula_has_zero_ratios() {
+        let analysis = FormulaAnalysis::new(&Formula::new(0));
+
+        assert!(analysis.clause_variable_ratio.abs() < f64::EPSILON);
+        assert!(analysis.horn_fraction.abs() < f64::EPSILON);
+        assert_eq!(analysis.variable_occurrences.maximum, 0);
+    }
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A handle to stop a running search from another thread, for instance a signal handler.
+/// Clones share the same state, so cancelling any of them cancels them all.
+/// The search checks the handle between generations and returns the best individual
+/// it has found so far
+#[derive(Clone, Debug, Default)]
+pub struct Cancellation {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Cancellation {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cancelling_a_clone_cancels_the_original() {
+        let cancellation = Cancellation::new();
+        let clone = cancellation.clone();
+
+        assert!(!cancellation.is_cancelled());
+        clone.cancel();
+        assert!(cancellation.is_cancelled());
+    }
+}
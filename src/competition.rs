@@ -0,0 +1,123 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use crate::sat::{Formula, Literal, Solution};
+
+/// The longest a value line of the model can be, including its leading `v`
+const MAXIMUM_LINE_LENGTH: usize = 80;
+
+/// The answer reported on the `s` line of the SAT competition output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Satisfiable,
+    /// Only reported for formulas containing an empty clause, the genetic algorithm
+    /// cannot prove that other formulas are unsatisfiable
+    Unsatisfiable,
+    Unknown,
+}
+
+impl Status {
+    #[must_use]
+    pub fn new(formula: &Formula, best_solution: &Solution) -> Self {
+        if formula.clauses().any(<[Literal]>::is_empty) {
+            Status::Unsatisfiable
+        } else if formula
+            .clauses()
+            .all(|clause| best_solution.satisfies_clause(clause))
+        {
+            Status::Satisfiable
+        } else {
+            Status::Unknown
+        }
+    }
+
+    /// The exit code of a SAT competition solver reaching this answer
+    #[must_use]
+    pub fn exit_code(self) -> ExitCode {
+        match self {
+            Status::Satisfiable => ExitCode::from(10),
+            Status::Unsatisfiable => ExitCode::from(20),
+            Status::Unknown => ExitCode::from(0),
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Satisfiable => write!(f, "SATISFIABLE"),
+            Status::Unsatisfiable => write!(f, "UNSATISFIABLE"),
+            Status::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+/// Writes the value of every variable of `solution` as a dimacs literal on `v` lines
+/// of at most `MAXIMUM_LINE_LENGTH` characters, the model ending with a 0
+///
+/// # Errors
+///
+/// Returns any error of the writer
+pub fn write_model(writer: &mut impl Write, solution: &Solution) -> io::Result<()> {
+    let literals = solution
+        .iter()
+        .enumerate()
+        .map(|(variable, value)| Literal::new(variable, value).to_string())
+        .chain(std::iter::once(String::from("0")));
+
+    let mut line = String::from("v");
+    for literal in literals {
+        if line.len() + 1 + literal.len() > MAXIMUM_LINE_LENGTH {
+            writeln!(writer, "{line}")?;
+            line = String::from("v");
+        }
+        line.push(' ');
+        line.push_str(&literal);
+    }
+    writeln!(writer, "{line}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn write_model_wraps_the_literals_and_ends_with_a_zero() {
+        let solution: Solution = (0..40).map(|variable| variable % 3 == 0).collect();
+        let mut output = Vec::new();
+
+        write_model(&mut output, &solution).expect("Writing to a vector should not fail");
+
+        let output = String::from_utf8(output).expect("The model should be valid utf-8");
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines.len() > 1);
+        assert!(
+            lines
+                .iter()
+                .all(|line| line.starts_with("v ") && line.len() <= MAXIMUM_LINE_LENGTH)
+        );
+        let literals: Vec<&str> = lines
+            .iter()
+            .flat_map(|line| line.split_whitespace().skip(1))
+            .collect();
+        assert_eq!(literals.len(), 41);
+        assert_eq!(&literals[..4], ["1", "-2", "-3", "4"]);
+        assert_eq!(literals.last(), Some(&"0"));
+    }
+
+    #[test]
+    fn status_only_reports_unsatisfiable_formulas_with_an_empty_clause() {
+        let mut formula = Formula::new(2);
+        formula.push_clause(&[Literal::new(0, true), Literal::new(1, false)]);
+        let solution = Solution::from([true, true]);
+
+        assert_eq!(Status::new(&formula, &solution), Status::Satisfiable);
+        assert_eq!(
+            Status::new(&formula, &Solution::from([false, true])),
+            Status::Unknown
+        );
+        formula.push_clause(&[]);
+        assert_eq!(Status::new(&formula, &solution), Status::Unsatisfiable);
+    }
+}
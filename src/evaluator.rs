@@ -0,0 +1,294 @@
+use crate::sat::{Formula, Literal, Solution};
+
+/// For each literal, the indices of the clauses it appears in
+#[derive(Debug)]
+pub struct Occurrences {
+    offsets: Vec<usize>,
+    clause_indices: Vec<usize>,
+}
+
+impl Occurrences {
+    #[must_use]
+    pub fn new(formula: &Formula) -> Self {
+        let mut counts = vec![0; 2 * formula.number_of_variables + 1];
+        for clause in formula.clauses() {
+            for literal in clause {
+                counts[literal.index() + 1] += 1;
+            }
+        }
+
+        let mut offsets = counts;
+        for literal_index in 1..offsets.len() {
+            offsets[literal_index] += offsets[literal_index - 1];
+        }
+
+        let mut next_positions = offsets.clone();
+        let mut clause_indices = vec![0; offsets[offsets.len() - 1]];
+        for (clause_index, clause) in formula.clauses().enumerate() {
+            for literal in clause {
+                clause_indices[next_positions[literal.index()]] = clause_index;
+                next_positions[literal.index()] += 1;
+            }
+        }
+
+        Occurrences {
+            offsets,
+            clause_indices,
+        }
+    }
+
+    #[must_use]
+    pub fn of(&self, literal: Literal) -> &[usize] {
+        &self.clause_indices[self.offsets[literal.index()]..self.offsets[literal.index() + 1]]
+    }
+}
+
+/// Tracks how many literals of each clause a solution satisfies, so that flipping
+/// a variable only revisits the clauses that variable appears in.
+/// It serves the local searches and focused mutation, which flip one variable at a time;
+/// populations are still scored from scratch, as a child of crossover has no counts to update
+#[derive(Debug, Clone)]
+pub struct IncrementalEvaluator<'a> {
+    formula: &'a Formula,
+    occurrences: &'a Occurrences,
+    solution: Solution,
+    true_literal_counts: Vec<usize>,
+    unsatisfied_clauses: Vec<usize>,
+    /// Position of each clause in `unsatisfied_clauses`, `None` when it is satisfied
+    unsatisfied_positions: Vec<Option<usize>>,
+}
+
+impl<'a> IncrementalEvaluator<'a> {
+    #[must_use]
+    pub fn new(formula: &'a Formula, occurrences: &'a Occurrences, solution: Solution) -> Self {
+        let mut evaluator = IncrementalEvaluator {
+            formula,
+            occurrences,
+            solution,
+            true_literal_counts: Vec::with_capacity(formula.number_of_clauses()),
+            unsatisfied_clauses: Vec::new(),
+            unsatisfied_positions: vec![None; formula.number_of_clauses()],
+        };
+        for (clause_index, clause) in formula.clauses().enumerate() {
+            let true_literal_count = clause
+                .iter()
+                .filter(|literal| evaluator.solution.get(literal.variable()) == literal.value())
+                .count();
+            evaluator.true_literal_counts.push(true_literal_count);
+            if true_literal_count == 0 {
+                evaluator.mark_unsatisfied(clause_index);
+            }
+        }
+        evaluator
+    }
+
+    /// Flips the value of `variable` and updates the clauses it appears in
+    pub fn flip(&mut self, variable: usize) {
+        let previous_value = self.solution.get(variable);
+        self.solution.flip(variable);
+
+        let occurrences = self.occurrences;
+        for &clause_index in occurrences.of(Literal::new(variable, !previous_value)) {
+            self.true_literal_counts[clause_index] += 1;
+            if self.true_literal_counts[clause_index] == 1 {
+                self.mark_satisfied(clause_index);
+            }
+        }
+        for &clause_index in occurrences.of(Literal::new(variable, previous_value)) {
+            self.true_literal_counts[clause_index] -= 1;
+            if self.true_literal_counts[clause_index] == 0 {
+                self.mark_unsatisfied(clause_index);
+            }
+        }
+    }
+
+    /// The ratio of clauses the current solution satisfies, as computed by `Solution::evaluate`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the formula has more clauses than `u32::MAX`
+    #[must_use]
+    pub fn fitness(&self) -> f64 {
+        let number_of_clauses = self.formula.number_of_clauses();
+        if number_of_clauses == 0 {
+            return 1.;
+        }
+        f64::from(
+            u32::try_from(number_of_clauses - self.unsatisfied_clauses.len())
+                .expect("The number of clauses should be less than the maximum value of u32"),
+        ) / f64::from(
+            u32::try_from(number_of_clauses)
+                .expect("The number of clauses should be less than the maximum value of u32"),
+        )
+    }
+
+    /// The number of clauses that flipping `variable` would satisfy
+    #[must_use]
+    pub fn make_count(&self, variable: usize) -> usize {
+        let false_literal = Literal::new(variable, !self.solution.get(variable));
+        self.occurrences
+            .of(false_literal)
+            .iter()
+            .filter(|&&clause_index| self.true_literal_counts[clause_index] == 0)
+            .count()
+    }
+
+    /// The number of clauses that flipping `variable` would leave unsatisfied
+    #[must_use]
+    pub fn break_count(&self, variable: usize) -> usize {
+        let true_literal = Literal::new(variable, self.solution.get(variable));
+        self.occurrences
+            .of(true_literal)
+            .iter()
+            .filter(|&&clause_index| self.true_literal_counts[clause_index] == 1)
+            .count()
+    }
+
+    /// The indices of the clauses the current solution does not satisfy, in no particular order
+    #[must_use]
+    pub fn unsatisfied_clauses(&self) -> &[usize] {
+        &self.unsatisfied_clauses
+    }
+
+    #[must_use]
+    pub fn solution(&self) -> &Solution {
+        &self.solution
+    }
+
+    #[must_use]
+    pub fn into_solution(self) -> Solution {
+        self.solution
+    }
+
+    fn mark_unsatisfied(&mut self, clause_index: usize) {
+        self.unsatisfied_positions[clause_index] = Some(self.unsatisfied_clauses.len());
+        self.unsatisfied_clauses.push(clause_index);
+    }
+
+    fn mark_satisfied(&mut self, clause_index: usize) {
+        let position = self.unsatisfied_positions[clause_index]
+            .take()
+            .expect("A clause becoming satisfied should have been unsatisfied");
+        self.unsatisfied_clauses.swap_remove(position);
+        if let Some(&moved_clause_index) = self.unsatisfied_clauses.get(position) {
+            self.unsatisfied_positions[moved_clause_index] = Some(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{RngExt, SeedableRng};
+
+    fn formula(number_of_variables: usize, clauses: &[&[(usize, bool)]]) -> Formula {
+        let mut formula = Formula::new(number_of_variables);
+        for clause in clauses {
+            let literals: Vec<Literal> = clause
+                .iter()
+                .map(|&(variable, value)| Literal::new(variable, value))
+                .collect();
+            formula.push_clause(&literals);
+        }
+        formula
+    }
+
+    #[test]
+    fn occurrences_should_list_the_clauses_of_each_literal() {
+        let formula = formula(2, &[&[(0, true), (1, false)], &[(0, true)], &[(1, true)]]);
+
+        let occurrences = Occurrences::new(&formula);
+
+        assert_eq!(occurrences.of(Literal::new(0, true)), &[0, 1]);
+        assert!(occurrences.of(Literal::new(0, false)).is_empty());
+        assert_eq!(occurrences.of(Literal::new(1, false)), &[0]);
+        assert_eq!(occurrences.of(Literal::new(1, true)), &[2]);
+    }
+
+    #[test]
+    fn new_should_find_the_unsatisfied_clauses() {
+        let formula = formula(2, &[&[(0, true), (1, false)], &[(0, true)], &[(1, true)]]);
+        let occurrences = Occurrences::new(&formula);
+
+        let evaluator =
+            IncrementalEvaluator::new(&formula, &occurrences, Solution::from([false, true]));
+
+        assert_eq!(evaluator.unsatisfied_clauses(), &[0, 1]);
+        assert!((evaluator.fitness() - 1. / 3.).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn flip_should_update_the_unsatisfied_clauses() {
+        let formula = formula(2, &[&[(0, true), (1, false)], &[(0, true)], &[(1, true)]]);
+        let occurrences = Occurrences::new(&formula);
+        let mut evaluator =
+            IncrementalEvaluator::new(&formula, &occurrences, Solution::from([false, true]));
+
+        evaluator.flip(0);
+
+        assert!(evaluator.unsatisfied_clauses().is_empty());
+        evaluator.flip(1);
+        assert_eq!(evaluator.unsatisfied_clauses(), &[2]);
+        assert_eq!(evaluator.solution(), &Solution::from([true, false]));
+    }
+
+    #[test]
+    fn make_and_break_counts_should_match_the_effect_of_a_flip() {
+        let formula = formula(
+            3,
+            &[
+                &[(0, true), (1, false)],
+                &[(0, true)],
+                &[(1, true), (2, true)],
+            ],
+        );
+        let occurrences = Occurrences::new(&formula);
+        let evaluator =
+            IncrementalEvaluator::new(&formula, &occurrences, Solution::from([false, true, false]));
+
+        assert_eq!(evaluator.make_count(0), 2);
+        assert_eq!(evaluator.break_count(0), 0);
+        assert_eq!(evaluator.make_count(1), 1);
+        assert_eq!(evaluator.break_count(1), 1);
+        assert_eq!(evaluator.make_count(2), 0);
+        assert_eq!(evaluator.break_count(2), 0);
+    }
+
+    #[test]
+    fn flip_should_keep_the_fitness_equal_to_a_full_evaluation() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let number_of_variables = 20;
+        let clauses: Vec<Vec<(usize, bool)>> = (0..80)
+            .map(|_| {
+                (0..3)
+                    .map(|_| (rng.random_range(0..number_of_variables), rng.random()))
+                    .collect()
+            })
+            .collect();
+        let clauses: Vec<&[(usize, bool)]> = clauses.iter().map(Vec::as_slice).collect();
+        let formula = formula(number_of_variables, &clauses);
+        let occurrences = Occurrences::new(&formula);
+        let mut evaluator = IncrementalEvaluator::new(
+            &formula,
+            &occurrences,
+            Solution::random(number_of_variables, &mut rng),
+        );
+
+        for _ in 0..200 {
+            evaluator.flip(rng.random_range(0..number_of_variables));
+
+            let solution = evaluator.solution();
+            assert!((evaluator.fitness() - solution.evaluate(&formula)).abs() < f64::EPSILON);
+            let mut unsatisfied_clauses = evaluator.unsatisfied_clauses().to_vec();
+            unsatisfied_clauses.sort_unstable();
+            let expected_unsatisfied_clauses: Vec<usize> = formula
+                .clauses()
+                .enumerate()
+                .filter(|(_, clause)| !solution.satisfies_clause(clause))
+                .map(|(clause_index, _)| clause_index)
+                .collect();
+            assert_eq!(unsatisfied_clauses, expected_unsatisfied_clauses);
+        }
+    }
+}
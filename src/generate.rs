@@ -0,0 +1,82 @@
+use rand::Rng;
+use rand::RngExt;
+use rand::seq::index;
+
+use crate::sat::{Formula, Literal, Solution};
+
+/// A uniform random k-SAT formula, each clause made of `clause_length` distinct variables
+/// drawn uniformly at random and negated with probability 1/2. With a planted solution,
+/// clauses it does not satisfy are drawn again, so that the formula is satisfiable
+///
+/// # Panics
+///
+/// Panics if the clause length is above the number of variables, or if the planted
+/// solution does not have a value for each variable
+#[must_use]
+pub fn random_k_sat(
+    number_of_variables: usize,
+    number_of_clauses: usize,
+    clause_length: usize,
+    planted_solution: Option<&Solution>,
+    rng: &mut dyn Rng,
+) -> Formula {
+    assert!(
+        clause_length <= number_of_variables,
+        "The clauses cannot have more distinct variables than the formula"
+    );
+    assert!(
+        planted_solution.is_none_or(|solution| solution.len() == number_of_variables),
+        "The planted solution should have a value for each variable"
+    );
+
+    let mut formula = Formula::new(number_of_variables);
+    let mut clause = Vec::with_capacity(clause_length);
+    while formula.number_of_clauses() < number_of_clauses {
+        clause.clear();
+        for variable in index::sample(rng, number_of_variables, clause_length) {
+            clause.push(Literal::new(variable, rng.random()));
+        }
+        if planted_solution.is_none_or(|solution| solution.satisfies_clause(&clause)) {
+            formula.push_clause(&clause);
+        }
+    }
+    formula
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn random_k_sat_should_draw_clauses_of_distinct_variables() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let formula = random_k_sat(20, 100, 4, None, &mut rng);
+
+        assert_eq!(formula.number_of_variables, 20);
+        assert_eq!(formula.number_of_clauses(), 100);
+        for clause in formula.clauses() {
+            assert_eq!(clause.len(), 4);
+            for (index, literal) in clause.iter().enumerate() {
+                assert!(
+                    clause[index + 1..]
+                        .iter()
+                        .all(|other| other.variable() != literal.variable())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn random_k_sat_should_be_satisfied_by_the_planted_solution() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let planted_solution = Solution::random(50, &mut rng);
+
+        let formula = random_k_sat(50, 300, 3, Some(&planted_solution), &mut rng);
+
+        assert_eq!(formula.number_of_clauses(), 300);
+        assert!((planted_solution.evaluate(&formula) - 1.).abs() < f64::EPSILON);
+    }
+}
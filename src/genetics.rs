@@ -1,56 +1,168 @@
-use rand::seq::{IndexedRandom, IteratorRandom, SliceRandom};
+pub mod crossover;
+pub mod fitness;
+pub mod islands;
+pub mod local_search;
+pub mod mutation;
+pub mod selection;
+pub mod steady_state;
 
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+
+use crate::cancellation::Cancellation;
+use crate::evaluator::Occurrences;
 use crate::sat::{Formula, Solution};
+use crossover::Crossover;
+use fitness::{ClauseWeights, FitnessFunction};
+use local_search::LocalSearch;
+use mutation::Mutation;
+use selection::Selection;
+use steady_state::Replacement;
+
+/// How each generation replaces the previous one
+#[derive(Clone, Copy, Debug)]
+pub enum Scheme {
+    /// The children of a generation replace the whole population, but for its elite
+    Generational,
+    /// Children are bred one at a time, each replacing a single individual
+    SteadyState(Replacement),
+}
+
+/// The settings of a run of the genetic algorithm
+pub struct Parameters {
+    pub population_size: u32,
+    pub maximum_number_of_generations: u32,
+    pub maximum_number_of_breeding_individuals: u32,
+    pub mutation_probability: f32,
+    pub selection: Box<dyn Selection>,
+    pub crossover: Box<dyn Crossover>,
+    pub mutation: Box<dyn Mutation>,
+    /// The number of fittest individuals copied unchanged into the next generation
+    /// of the generational scheme
+    pub elitism: u32,
+    pub scheme: Scheme,
+    /// Improves the children once they are mutated
+    pub local_search: Option<Box<dyn LocalSearch>>,
+    /// The number of fittest children of each generation the local search improves,
+    /// all of them when `None`. Every child is improved with the steady-state scheme
+    pub local_search_individuals: Option<u32>,
+    pub fitness: FitnessFunction,
+    /// Stop once the run has lasted this long, checked between generations
+    pub time_limit: Option<Duration>,
+    /// Stop once this many individuals have been evaluated
+    pub maximum_number_of_evaluations: Option<u64>,
+    /// Stop after this many generations without improving the all time best fitness
+    pub stagnation: Option<u32>,
+    /// Stop as soon as this handle is cancelled, checked between generations
+    pub cancellation: Cancellation,
+    /// Starts every progress message printed during the search
+    pub message_prefix: &'static str,
+    /// Print no progress message during the search
+    pub quiet: bool,
+}
+
+/// Why a run of the genetic algorithm stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    PerfectIndividual,
+    GenerationLimit,
+    EvaluationLimit,
+    TimeLimit,
+    Stagnation,
+    /// Another island of the island model found a perfect individual
+    PerfectIndividualOnAnotherIsland,
+    /// The cancellation handle of the parameters was cancelled
+    Cancelled,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Termination::PerfectIndividual => write!(f, "a perfect individual was found"),
+            Termination::GenerationLimit => {
+                write!(f, "the maximum number of generations was reached")
+            }
+            Termination::EvaluationLimit => {
+                write!(f, "the maximum number of evaluations was reached")
+            }
+            Termination::TimeLimit => write!(f, "the time limit was reached"),
+            Termination::Stagnation => write!(f, "the best fitness stopped improving"),
+            Termination::PerfectIndividualOnAnotherIsland => {
+                write!(f, "another island found a perfect individual")
+            }
+            Termination::Cancelled => write!(f, "the search was interrupted"),
+        }
+    }
+}
 
 struct Population {
     individuals: Vec<Solution>,
+    /// The ratio of clauses each individual satisfies, whatever the fitness function
+    satisfied_ratios: Vec<f64>,
+    /// The highest satisfied ratio of the population
     best_fitness: f64,
+    /// The number of local search flips it took to breed the population
+    flips: u64,
+    /// The number of individuals evaluated to breed the population, besides its own evaluation
+    evaluations: u64,
 }
 
 impl Population {
     fn new(individuals: Vec<Solution>) -> Self {
         Population {
             individuals,
+            satisfied_ratios: Vec::new(),
             best_fitness: 0_f64,
+            flips: 0,
+            evaluations: 0,
         }
     }
 
-    fn evaluate(&mut self, formula: &Formula) -> Vec<f64> {
-        let population_fitness = self
+    /// Returns the fitness of each individual given the clause weights,
+    /// and records the ratio of clauses each one satisfies
+    fn evaluate(&mut self, formula: &Formula, weights: &ClauseWeights) -> Vec<f64> {
+        let (population_fitness, satisfied_ratios): (Vec<f64>, Vec<f64>) = self
             .individuals
-            .iter()
-            .map(|solution| solution.evaluate(formula))
-            .collect::<Vec<_>>();
-        self.best_fitness = Population::best_fitness(&population_fitness);
+            .par_iter()
+            .map(|solution| weights.evaluate(solution, formula))
+            .unzip();
+        self.best_fitness = Population::best_fitness(&satisfied_ratios);
+        self.satisfied_ratios = satisfied_ratios;
         population_fitness
     }
 
     fn generate_random_individuals(
         individual_size: usize,
         number_of_individuals: u32,
+        rng: &mut dyn Rng,
     ) -> Vec<Solution> {
         (0..number_of_individuals)
-            .map(|_| {
-                let literals: Vec<bool> = (0..individual_size)
-                    .map(|_| rand::random::<bool>())
-                    .collect();
-                Solution { literals }
-            })
+            .map(|_| Solution::random(individual_size, rng))
             .collect()
     }
 
-    fn genesis(individual_size: usize, population_size: u32) -> Self {
-        let individuals = Population::generate_random_individuals(individual_size, population_size);
+    fn genesis(individual_size: usize, population_size: u32, rng: &mut dyn Rng) -> Self {
+        let individuals =
+            Population::generate_random_individuals(individual_size, population_size, rng);
         Population::new(individuals)
     }
 
     fn best_individual(&self, formula: &Formula) -> Solution {
+        let population_fitness: Vec<f64> = self
+            .individuals
+            .par_iter()
+            .map(|solution| solution.evaluate(formula))
+            .collect();
         let mut best_fitness = 0.;
         let mut best_individual_index = 0;
 
-        for (solution_index, solution) in self.individuals.iter().enumerate() {
-            let individual_fitness = solution.evaluate(formula);
-            if solution.evaluate(formula) > best_fitness {
+        for (solution_index, &individual_fitness) in population_fitness.iter().enumerate() {
+            if individual_fitness > best_fitness {
                 best_fitness = individual_fitness;
                 best_individual_index = solution_index;
             }
@@ -81,65 +193,55 @@ impl Population {
         individual_fitness_map
     }
 
+    /// The `number_of_elites` fittest individuals, fittest first
+    fn elite(&self, population_fitness: &[f64], number_of_elites: u32) -> Vec<Solution> {
+        let mut individual_indices: Vec<usize> = (0..self.individuals.len()).collect();
+        individual_indices.sort_by(|&first, &second| {
+            population_fitness[second].total_cmp(&population_fitness[first])
+        });
+        individual_indices
+            .into_iter()
+            .take(number_of_elites as usize)
+            .map(|individual_index| self.individuals[individual_index].clone())
+            .collect()
+    }
+
     fn select_breeding_population(
         indivudial_fitness_map: &[(Solution, f64)],
         number_of_breeding_individuals: u32,
+        selection: &dyn Selection,
+        rng: &mut dyn Rng,
     ) -> Vec<Solution> {
-        let mut rng = rand::rng();
-        indivudial_fitness_map
-            .sample_weighted(&mut rng, number_of_breeding_individuals as usize, |item| {
-                item.1
-            })
-            .expect("The choice based on the fitness should not fail, as the weights should all be within the unit interval")
-            .map(|item| item.0.clone())
-            .collect()
-    }
-
-    fn flip_random_literal(mut individual: Solution) -> Solution {
-        let mut rng = rand::rng();
-        let literal_index_to_flip = (0..individual.literals.len())
-            .choose(&mut rng)
-            .expect("An individual should have at least a single literal");
-        let literal_value_to_flip = individual.literals.remove(literal_index_to_flip);
-        individual
-            .literals
-            .insert(literal_index_to_flip, !literal_value_to_flip);
-        individual
+        selection.select(indivudial_fitness_map, number_of_breeding_individuals, rng)
     }
 
-    fn coupling(couples: Vec<(&Solution, &Solution)>) -> Vec<Solution> {
-        let mut embryos: Vec<Solution> = Vec::new();
-        for (first_parent, second_parent) in couples {
-            let embryo_literals: Vec<bool> = first_parent
-                .literals
-                .iter()
-                .zip(second_parent.literals.iter())
-                .map(|(first_parent_literal, second_parent_literal)| {
-                    if rand::random() {
-                        *first_parent_literal
-                    } else {
-                        *second_parent_literal
-                    }
-                })
-                .collect();
-            embryos.push(Solution {
-                literals: embryo_literals,
-            });
-        }
-        embryos
+    fn coupling(
+        couples: Vec<(&Solution, &Solution)>,
+        crossover: &dyn Crossover,
+        formula: &Formula,
+        rng: &mut dyn Rng,
+    ) -> Vec<Solution> {
+        couples
+            .into_iter()
+            .map(|(first_parent, second_parent)| {
+                crossover.cross(first_parent, second_parent, formula, rng)
+            })
+            .collect()
     }
 
     fn binary_crossover(
         mut breeding_individuals: Vec<Solution>,
         number_of_individuals: u32,
+        crossover: &dyn Crossover,
+        formula: &Formula,
+        rng: &mut dyn Rng,
     ) -> Vec<Solution> {
         let mut embryos: Vec<Solution> = Vec::new();
-        let mut rng = rand::rng();
         loop {
             if embryos.len() >= number_of_individuals as usize {
                 break;
             }
-            breeding_individuals.shuffle(&mut rng);
+            breeding_individuals.shuffle(rng);
             let couples: Vec<(&Solution, &Solution)> = breeding_individuals
                 .iter()
                 .enumerate()
@@ -153,18 +255,26 @@ impl Population {
                         .map(|(_, x)| x),
                 )
                 .collect();
-            embryos.append(&mut Population::coupling(couples));
+            embryos.append(&mut Population::coupling(couples, crossover, formula, rng));
         }
-        breeding_individuals
+        embryos.truncate(number_of_individuals as usize);
+        embryos
     }
 
-    fn mutation(embryos: Vec<Solution>, mutation_probability: f32) -> Vec<Solution> {
+    fn mutation(
+        embryos: Vec<Solution>,
+        mutation_probability: f32,
+        mutation: &dyn Mutation,
+        formula: &Formula,
+        occurrences: &Occurrences,
+        rng: &mut dyn Rng,
+    ) -> Vec<Solution> {
         let mut children: Vec<Solution> = Vec::new();
 
         for embryo in embryos {
-            let mutate: bool = rand::random::<f32>() > mutation_probability;
+            let mutate: bool = rng.random::<f32>() < mutation_probability;
             if mutate {
-                let mutated_child = Population::flip_random_literal(embryo);
+                let mutated_child = mutation.mutate(embryo, formula, occurrences, rng);
                 children.push(mutated_child);
             } else {
                 children.push(embryo);
@@ -173,112 +283,404 @@ impl Population {
         children
     }
 
+    /// Runs the local search on the `number_of_individuals` fittest children, or on all of them,
+    /// returning the children along with the number of flips the local search made.
+    /// Each local search draws from its own generator, seeded from `rng` in the order of the
+    /// children, so the result does not depend on how many threads run them
+    fn local_search(
+        mut children: Vec<Solution>,
+        local_search: &dyn LocalSearch,
+        number_of_individuals: Option<u32>,
+        formula: &Formula,
+        occurrences: &Occurrences,
+        rng: &mut dyn Rng,
+    ) -> (Vec<Solution>, u64) {
+        let mut improved_indices: Vec<usize> = (0..children.len()).collect();
+        if let Some(number_of_individuals) = number_of_individuals {
+            let children_fitness: Vec<f64> = children
+                .par_iter()
+                .map(|child| child.evaluate(formula))
+                .collect();
+            improved_indices.sort_by(|&first, &second| {
+                children_fitness[second].total_cmp(&children_fitness[first])
+            });
+            improved_indices.truncate(number_of_individuals as usize);
+        }
+
+        let seeds: Vec<u64> = improved_indices.iter().map(|_| rng.random()).collect();
+        let improved_children: Vec<(Solution, u64)> = improved_indices
+            .par_iter()
+            .zip(seeds)
+            .map(|(&child_index, seed)| {
+                local_search.improve(
+                    children[child_index].clone(),
+                    formula,
+                    occurrences,
+                    &mut StdRng::seed_from_u64(seed),
+                )
+            })
+            .collect();
+
+        let mut number_of_flips = 0;
+        for (child_index, (improved_child, flips)) in
+            improved_indices.into_iter().zip(improved_children)
+        {
+            children[child_index] = improved_child;
+            number_of_flips += flips;
+        }
+        (children, number_of_flips)
+    }
+
     fn next_generation(
         &self,
+        formula: &Formula,
+        occurrences: &Occurrences,
         population_fitness: &[f64],
-        maximum_number_of_breeding_individuals: u32,
-        number_of_individuals_in_generation: u32,
-        mutation_probability: f32,
+        parameters: &Parameters,
+        rng: &mut dyn Rng,
     ) -> Population {
+        let mut next_gen_individuals = self.elite(population_fitness, parameters.elitism);
+        let number_of_children = parameters.population_size
+            - u32::try_from(next_gen_individuals.len())
+                .expect("The elite is smaller than the population");
+
         let individual_fitness_map = self.map_fitness_to_individuals(population_fitness);
         if individual_fitness_map.len() <= 1 {
-            println!("The whole population died. Restarting from scratch");
-            return Population::genesis(
+            if !parameters.quiet {
+                println!(
+                    "{}The whole population died. Restarting from scratch",
+                    parameters.message_prefix
+                );
+            }
+            next_gen_individuals.append(&mut Population::generate_random_individuals(
                 self.individuals
                     .first()
                     .expect("A generation should always contain individuals")
-                    .literals
                     .len(),
-                number_of_individuals_in_generation,
-            );
+                number_of_children,
+                rng,
+            ));
+            return Population::new(next_gen_individuals);
         }
 
         let breeding_population = Population::select_breeding_population(
             &individual_fitness_map,
-            maximum_number_of_breeding_individuals,
+            parameters.maximum_number_of_breeding_individuals,
+            parameters.selection.as_ref(),
+            rng,
+        );
+        let embryos = Population::binary_crossover(
+            breeding_population,
+            number_of_children,
+            parameters.crossover.as_ref(),
+            formula,
+            rng,
+        );
+        let mut children = Population::mutation(
+            embryos,
+            parameters.mutation_probability,
+            parameters.mutation.as_ref(),
+            formula,
+            occurrences,
+            rng,
         );
-        let embryos =
-            Population::binary_crossover(breeding_population, number_of_individuals_in_generation);
-        let next_gen_individuals = Population::mutation(embryos, mutation_probability);
+        let mut number_of_flips = 0;
+        if let Some(local_search) = &parameters.local_search {
+            (children, number_of_flips) = Population::local_search(
+                children,
+                local_search.as_ref(),
+                parameters.local_search_individuals,
+                formula,
+                occurrences,
+                rng,
+            );
+        }
+        next_gen_individuals.append(&mut children);
 
-        Population::new(next_gen_individuals)
+        let mut next_population = Population::new(next_gen_individuals);
+        next_population.flips = number_of_flips;
+        next_population
     }
 }
 
-pub fn optimize(
-    formula: &Formula,
-    population_size: u32,
-    maximum_number_of_generations: u32,
-    maximum_number_of_breeding_individuals: u32,
-    mutation_probability: f32,
-) -> Solution {
-    let mut population = Population::genesis(formula.number_of_literals, population_size);
-    let mut all_time_best_fitness = 0_f64;
-    let mut all_time_best_individual = population
-        .individuals
-        .first()
-        .expect("Initial population should at least have a single individual")
-        .clone();
-
-    for generation in 1..maximum_number_of_generations {
-        let population_fitness = population.evaluate(formula);
-        let generation_best_fitness = population.best_fitness;
+/// The result of a run of the genetic algorithm
+#[derive(Debug)]
+pub struct Outcome {
+    pub best_individual: Solution,
+    pub best_fitness: f64,
+    /// The best fitness of each generation, in order
+    pub fitness_history: Vec<f64>,
+    /// The number of variables the local search flipped over the whole run
+    pub flips: u64,
+    /// The number of individuals evaluated over the whole run
+    pub evaluations: u64,
+    pub termination: Termination,
+    /// The weight of each clause at the end of the run, all 1 unless the weights adapted
+    pub clause_weights: Vec<f64>,
+}
+
+/// The state of a run of the genetic algorithm between two generations
+struct Search {
+    population: Population,
+    population_fitness: Vec<f64>,
+    weights: ClauseWeights,
+    /// The generation the population belongs to, starting at 1
+    generation: u32,
+    fitness_history: Vec<f64>,
+    all_time_best_fitness: f64,
+    all_time_best_individual: Solution,
+    flips: u64,
+    evaluations: u64,
+    perfect_individual_found: bool,
+    /// The last generation that improved the all time best fitness
+    last_improving_generation: u32,
+    started: Instant,
+    /// Prepended to the progress messages, to tell apart the islands of the island model
+    label: String,
+}
+
+impl Search {
+    fn new(formula: &Formula, parameters: &Parameters, label: &str, rng: &mut dyn Rng) -> Self {
+        let mut population =
+            Population::genesis(formula.number_of_variables, parameters.population_size, rng);
+        let all_time_best_individual = population
+            .individuals
+            .first()
+            .expect("Initial population should at least have a single individual")
+            .clone();
+        let weights = ClauseWeights::uniform(formula);
+        let population_fitness = population.evaluate(formula, &weights);
+
+        let mut search = Search {
+            population,
+            population_fitness,
+            weights,
+            generation: 1,
+            fitness_history: Vec::new(),
+            all_time_best_fitness: 0_f64,
+            all_time_best_individual,
+            flips: 0,
+            evaluations: u64::from(parameters.population_size),
+            perfect_individual_found: false,
+            last_improving_generation: 1,
+            started: Instant::now(),
+            label: format!("{}{label}", parameters.message_prefix),
+        };
+        search.record(formula, parameters);
+        search
+    }
+
+    /// Records the best fitness of the population, which was just scored,
+    /// so that the best individual of the last generation is never lost
+    fn record(&mut self, formula: &Formula, parameters: &Parameters) {
+        let generation = self.generation;
+        let label = &self.label;
+
+        let generation_best_fitness = self.population.best_fitness;
+        self.fitness_history.push(generation_best_fitness);
         if (generation_best_fitness - 1_f64).abs() < f64::EPSILON {
-            println!("Prefect individual found!");
-            all_time_best_individual = population.best_individual(formula);
-            break;
+            if !parameters.quiet {
+                println!("{label}Prefect individual found!");
+            }
+            self.all_time_best_fitness = generation_best_fitness;
+            self.all_time_best_individual = self.population.best_individual(formula);
+            self.perfect_individual_found = true;
+            return;
         }
-        if generation_best_fitness > all_time_best_fitness {
+        if generation_best_fitness > self.all_time_best_fitness {
+            if !parameters.quiet {
+                println!(
+                    "{label}During generation {generation} new all time best fitness has been found: {generation_best_fitness}"
+                );
+            }
+            self.all_time_best_fitness = generation_best_fitness;
+            self.all_time_best_individual = self.population.best_individual(formula);
+            self.last_improving_generation = generation;
+        } else if generation.is_multiple_of(100) && !parameters.quiet {
             println!(
-                "During generation {generation} new all time best fitness has been found: {generation_best_fitness}"
+                "{label}The generation {generation}. All time best fitness {}",
+                self.all_time_best_fitness
             );
-            all_time_best_fitness = generation_best_fitness;
-            all_time_best_individual = population.best_individual(formula);
-        } else if generation % 100 == 0 {
-            println!("The generation {generation}. All time best fitness {all_time_best_fitness}");
         }
+    }
 
-        population = population.next_generation(
-            &population_fitness,
-            maximum_number_of_breeding_individuals,
-            population_size,
-            mutation_probability,
-        );
+    /// Why the search should stop, `None` while it should go on
+    fn termination(&self, parameters: &Parameters) -> Option<Termination> {
+        if self.perfect_individual_found {
+            Some(Termination::PerfectIndividual)
+        } else if parameters.cancellation.is_cancelled() {
+            Some(Termination::Cancelled)
+        } else if self.generation >= parameters.maximum_number_of_generations {
+            Some(Termination::GenerationLimit)
+        } else if parameters.maximum_number_of_evaluations.is_some_and(
+            |maximum_number_of_evaluations| self.evaluations >= maximum_number_of_evaluations,
+        ) {
+            Some(Termination::EvaluationLimit)
+        } else if parameters
+            .time_limit
+            .is_some_and(|time_limit| self.started.elapsed() >= time_limit)
+        {
+            Some(Termination::TimeLimit)
+        } else if parameters.stagnation.is_some_and(|stagnation| {
+            self.generation - self.last_improving_generation >= stagnation
+        }) {
+            Some(Termination::Stagnation)
+        } else {
+            None
+        }
+    }
+
+    fn is_over(&self, parameters: &Parameters) -> bool {
+        self.termination(parameters).is_some()
+    }
+
+    /// Breeds and records the next generation, unless the search is over
+    fn advance(
+        &mut self,
+        formula: &Formula,
+        occurrences: &Occurrences,
+        parameters: &Parameters,
+        rng: &mut dyn Rng,
+    ) {
+        if self.is_over(parameters) {
+            return;
+        }
+        let generation = self.generation;
+
+        if let FitnessFunction::SteppedAdaptationOfWeights {
+            interval,
+            increment,
+        } = parameters.fitness
+            && generation.is_multiple_of(interval)
+        {
+            self.weights.increase_unsatisfied(
+                &self.population.best_individual(formula),
+                formula,
+                increment,
+            );
+            self.population_fitness = self.population.evaluate(formula, &self.weights);
+            self.evaluations += u64::from(parameters.population_size);
+        }
+
+        let population = std::mem::replace(&mut self.population, Population::new(Vec::new()));
+        let population_fitness = std::mem::take(&mut self.population_fitness);
+        (self.population, self.population_fitness) = match parameters.scheme {
+            Scheme::Generational => {
+                let mut next_population = population.next_generation(
+                    formula,
+                    occurrences,
+                    &population_fitness,
+                    parameters,
+                    rng,
+                );
+                let next_population_fitness = next_population.evaluate(formula, &self.weights);
+                (next_population, next_population_fitness)
+            }
+            Scheme::SteadyState(replacement) => population.steady_state_generation(
+                formula,
+                occurrences,
+                &self.weights,
+                population_fitness,
+                parameters,
+                replacement,
+                rng,
+            ),
+        };
+        self.flips += self.population.flips;
+        self.evaluations += self.population.evaluations;
+        if let Scheme::Generational = parameters.scheme {
+            self.evaluations += u64::from(parameters.population_size);
+        }
+        self.generation += 1;
+        self.record(formula, parameters);
     }
 
-    all_time_best_individual
+    fn into_outcome(self, termination: Termination) -> Outcome {
+        Outcome {
+            best_individual: self.all_time_best_individual,
+            best_fitness: self.all_time_best_fitness,
+            fitness_history: self.fitness_history,
+            flips: self.flips,
+            evaluations: self.evaluations,
+            termination,
+            clause_weights: self.weights.weights().to_vec(),
+        }
+    }
+}
+
+/// Runs the genetic algorithm, drawing all its randomness from `rng`
+/// so that a run can be reproduced from the seed of the generator.
+/// Evaluations and local searches run on the current rayon thread pool,
+/// without changing the outcome of the run
+pub fn optimize(formula: &Formula, parameters: &Parameters, rng: &mut dyn Rng) -> Outcome {
+    let occurrences = Occurrences::new(formula);
+    let mut search = Search::new(formula, parameters, "", rng);
+    loop {
+        if let Some(termination) = search.termination(parameters) {
+            return search.into_outcome(termination);
+        }
+        search.advance(formula, &occurrences, parameters, rng);
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::parse_dimacs::{self, Simplification, Strictness};
+    use crate::sat::Literal;
+    use crossover::Uniform;
+    use local_search::{Greedy, WalkSat};
+    use mutation::KFlip;
+    use selection::Roulette;
+
+    fn random_3_sat_formula(
+        number_of_variables: usize,
+        number_of_clauses: usize,
+        seed: u64,
+    ) -> Formula {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut formula = Formula::new(number_of_variables);
+        for _ in 0..number_of_clauses {
+            let clause: Vec<Literal> = (0..3)
+                .map(|_| Literal::new(rng.random_range(0..number_of_variables), rng.random()))
+                .collect();
+            formula.push_clause(&clause);
+        }
+        formula
+    }
+
+    fn parameters(population_size: u32, maximum_number_of_generations: u32) -> Parameters {
+        Parameters {
+            population_size,
+            maximum_number_of_generations,
+            maximum_number_of_breeding_individuals: 10,
+            mutation_probability: 0.2,
+            selection: Box::new(Roulette),
+            crossover: Box::new(Uniform),
+            mutation: Box::new(KFlip { flips: 1 }),
+            elitism: 0,
+            scheme: Scheme::Generational,
+            local_search: None,
+            local_search_individuals: None,
+            fitness: FitnessFunction::SatisfiedRatio,
+            time_limit: None,
+            maximum_number_of_evaluations: None,
+            stagnation: None,
+            cancellation: Cancellation::default(),
+            message_prefix: "",
+            quiet: false,
+        }
+    }
 
     #[test]
     fn test_map_fitness_to_individual_creates_a_map_of_each_individual_and_its_fitness() {
-        let indivudials = vec![
-            Solution {
-                literals: vec![true, false],
-            },
-            Solution {
-                literals: vec![false, true],
-            },
-        ];
+        let indivudials = vec![Solution::from([true, false]), Solution::from([false, true])];
         let population = Population::new(indivudials);
         let population_fitness = vec![0.1, 0.9];
 
         let expected_individual_fitness_map = [
-            (
-                Solution {
-                    literals: vec![true, false],
-                },
-                0.1,
-            ),
-            (
-                Solution {
-                    literals: vec![false, true],
-                },
-                0.9,
-            ),
+            (Solution::from([true, false]), 0.1),
+            (Solution::from([false, true]), 0.9),
         ];
 
         let individual_fitness_map = population.map_fitness_to_individuals(&population_fitness);
@@ -315,23 +717,11 @@ mod test {
 
     #[test]
     fn test_map_fitness_to_individual_should_drop_indivudials_with_0_fitness() {
-        let individuals = vec![
-            Solution {
-                literals: vec![true, false],
-            },
-            Solution {
-                literals: vec![false, true],
-            },
-        ];
+        let individuals = vec![Solution::from([true, false]), Solution::from([false, true])];
         let population = Population::new(individuals);
         let population_fitness = vec![0., 0.5];
 
-        let expected_individual_fitness_map = [(
-            Solution {
-                literals: vec![false, true],
-            },
-            0.5,
-        )];
+        let expected_individual_fitness_map = [(Solution::from([false, true]), 0.5)];
 
         let individual_fitness_map = population.map_fitness_to_individuals(&population_fitness);
 
@@ -344,14 +734,7 @@ mod test {
     #[test]
     fn test_choose_individuals_to_breed_returns_the_whole_input_population_when_number_of_bredding_indivudials_equals_population_size()
      {
-        let individuals = [
-            Solution {
-                literals: vec![true, false],
-            },
-            Solution {
-                literals: vec![false, true],
-            },
-        ];
+        let individuals = [Solution::from([true, false]), Solution::from([false, true])];
         let individual_fitness_map =
             vec![(individuals[0].clone(), 0.5), (individuals[1].clone(), 0.5)];
         let number_of_breeding_individuals = 2;
@@ -359,6 +742,8 @@ mod test {
         let breeding_population = Population::select_breeding_population(
             &individual_fitness_map,
             number_of_breeding_individuals,
+            &Roulette,
+            &mut rand::rng(),
         );
 
         assert!(
@@ -373,30 +758,17 @@ mod test {
     fn test_choose_individuals_to_breed_returns_a_vector_of_individuals_with_a_len_equal_to_number_of_breeding_individual()
      {
         let individual_fitness_map = vec![
-            (
-                Solution {
-                    literals: vec![true, false],
-                },
-                0.5,
-            ),
-            (
-                Solution {
-                    literals: vec![false, true],
-                },
-                0.5,
-            ),
-            (
-                Solution {
-                    literals: vec![true, true],
-                },
-                0.4,
-            ),
+            (Solution::from([true, false]), 0.5),
+            (Solution::from([false, true]), 0.5),
+            (Solution::from([true, true]), 0.4),
         ];
         let number_of_breeding_individuals = 2;
 
         let breeding_population = Population::select_breeding_population(
             &individual_fitness_map,
             number_of_breeding_individuals,
+            &Roulette,
+            &mut rand::rng(),
         );
 
         assert_eq!(
@@ -414,6 +786,8 @@ mod test {
         let breeding_population = Population::select_breeding_population(
             &individual_fitness_map,
             number_of_breeding_individuals,
+            &Roulette,
+            &mut rand::rng(),
         );
 
         assert_eq!(breeding_population.len(), 0);
@@ -422,19 +796,443 @@ mod test {
     #[test]
     fn test_choose_individuals_to_breed_returns_an_individual_vector_with_the_same_length_as_individual_fitness_map_when_it_is_smaller_than_number_of_breeding_population()
      {
-        let individual_fitness_map = vec![(
-            Solution {
-                literals: vec![true, false],
-            },
-            0.5,
-        )];
+        let individual_fitness_map = vec![(Solution::from([true, false]), 0.5)];
         let number_of_breeding_individuals = 2;
 
         let breeding_population = Population::select_breeding_population(
             &individual_fitness_map,
             number_of_breeding_individuals,
+            &Roulette,
+            &mut rand::rng(),
         );
 
         assert_eq!(breeding_population.len(), individual_fitness_map.len());
     }
+
+    #[test]
+    fn optimize_should_reproduce_a_run_given_the_same_seed() {
+        let formula = random_3_sat_formula(50, 215, 0);
+
+        let parameters = parameters(20, 30);
+
+        let first_outcome = optimize(&formula, &parameters, &mut StdRng::seed_from_u64(42));
+        let second_outcome = optimize(&formula, &parameters, &mut StdRng::seed_from_u64(42));
+
+        assert_eq!(
+            first_outcome.best_individual,
+            second_outcome.best_individual
+        );
+        assert_eq!(
+            first_outcome.fitness_history,
+            second_outcome.fitness_history
+        );
+    }
+
+    #[test]
+    fn next_generation_should_preserve_the_population_size() {
+        let formula = random_3_sat_formula(30, 120, 1);
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut population = Population::genesis(formula.number_of_variables, 25, &mut rng);
+        let parameters = parameters(25, 20);
+
+        for _ in 0..20 {
+            let population_fitness =
+                population.evaluate(&formula, &ClauseWeights::uniform(&formula));
+            population = population.next_generation(
+                &formula,
+                &Occurrences::new(&formula),
+                &population_fitness,
+                &parameters,
+                &mut rng,
+            );
+
+            assert_eq!(population.individuals.len(), 25);
+        }
+    }
+
+    #[test]
+    fn next_generation_should_keep_the_elite_unchanged() {
+        let formula = random_3_sat_formula(30, 120, 5);
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut population = Population::genesis(formula.number_of_variables, 25, &mut rng);
+        let parameters = Parameters {
+            elitism: 3,
+            ..parameters(25, 20)
+        };
+        let population_fitness = population.evaluate(&formula, &ClauseWeights::uniform(&formula));
+        let elite = population.elite(&population_fitness, 3);
+
+        let next_population = population.next_generation(
+            &formula,
+            &Occurrences::new(&formula),
+            &population_fitness,
+            &parameters,
+            &mut rng,
+        );
+
+        assert_eq!(next_population.individuals.len(), 25);
+        assert_eq!(next_population.individuals[..3], elite[..]);
+    }
+
+    #[test]
+    fn optimize_should_never_lose_the_best_fitness_with_elitism() {
+        let formula = random_3_sat_formula(60, 300, 6);
+        let mut rng = StdRng::seed_from_u64(6);
+        let parameters = Parameters {
+            mutation_probability: 0.9,
+            elitism: 1,
+            ..parameters(30, 200)
+        };
+
+        let outcome = optimize(&formula, &parameters, &mut rng);
+
+        assert!(
+            outcome
+                .fitness_history
+                .windows(2)
+                .all(|pair| pair[0] <= pair[1])
+        );
+    }
+
+    #[test]
+    fn local_search_should_only_improve_the_requested_number_of_fittest_children() {
+        let formula = random_3_sat_formula(40, 170, 7);
+        let occurrences = Occurrences::new(&formula);
+        let mut rng = StdRng::seed_from_u64(7);
+        let children = Population::generate_random_individuals(40, 10, &mut rng);
+        let children_fitness: Vec<f64> = children
+            .iter()
+            .map(|child| child.evaluate(&formula))
+            .collect();
+        let fittest_child_index = (0..children.len())
+            .max_by(|&first, &second| children_fitness[first].total_cmp(&children_fitness[second]))
+            .expect("There are children");
+
+        let (improved_children, _) = Population::local_search(
+            children.clone(),
+            &Greedy {
+                maximum_number_of_flips: 1_000,
+            },
+            Some(1),
+            &formula,
+            &occurrences,
+            &mut rng,
+        );
+
+        for (child_index, (child, improved_child)) in
+            children.iter().zip(&improved_children).enumerate()
+        {
+            if child_index == fittest_child_index {
+                assert!(improved_child.evaluate(&formula) >= child.evaluate(&formula));
+            } else {
+                assert_eq!(improved_child, child);
+            }
+        }
+    }
+
+    #[test]
+    fn optimize_should_report_the_local_search_flips() {
+        let formula = random_3_sat_formula(40, 170, 8);
+        let mut rng = StdRng::seed_from_u64(8);
+        let parameters = Parameters {
+            local_search: Some(Box::new(WalkSat {
+                maximum_number_of_flips: 20,
+                noise: 0.5,
+            })),
+            ..parameters(10, 5)
+        };
+
+        let outcome = optimize(&formula, &parameters, &mut rng);
+
+        assert!(outcome.flips > 0);
+        assert!(outcome.flips <= 20 * 10 * 5);
+    }
+
+    #[test]
+    fn optimize_should_adapt_the_weights_of_unsatisfied_clauses() {
+        let formula = random_3_sat_formula(60, 300, 9);
+        let mut rng = StdRng::seed_from_u64(9);
+        let parameters = Parameters {
+            fitness: FitnessFunction::SteppedAdaptationOfWeights {
+                interval: 2,
+                increment: 1.,
+            },
+            ..parameters(10, 11)
+        };
+
+        let outcome = optimize(&formula, &parameters, &mut rng);
+
+        assert_eq!(outcome.clause_weights.len(), formula.number_of_clauses());
+        assert!(outcome.clause_weights.iter().all(|&weight| weight >= 1.));
+        assert!(outcome.clause_weights.iter().any(|&weight| weight > 1.));
+        assert!(outcome.clause_weights.iter().all(|&weight| weight <= 6.));
+    }
+
+    #[test]
+    fn optimize_should_not_depend_on_the_number_of_threads() {
+        let formula = random_3_sat_formula(60, 300, 10);
+        let run_with_threads = |number_of_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(number_of_threads)
+                .build()
+                .expect("The thread pool should build")
+                .install(|| {
+                    let parameters = Parameters {
+                        local_search: Some(Box::new(WalkSat {
+                            maximum_number_of_flips: 20,
+                            noise: 0.5,
+                        })),
+                        local_search_individuals: Some(4),
+                        ..parameters(12, 15)
+                    };
+                    optimize(&formula, &parameters, &mut StdRng::seed_from_u64(10))
+                })
+        };
+
+        let single_threaded_outcome = run_with_threads(1);
+        let multi_threaded_outcome = run_with_threads(4);
+
+        assert_eq!(
+            single_threaded_outcome.best_individual,
+            multi_threaded_outcome.best_individual
+        );
+        assert_eq!(
+            single_threaded_outcome.fitness_history,
+            multi_threaded_outcome.fitness_history
+        );
+        assert_eq!(single_threaded_outcome.flips, multi_threaded_outcome.flips);
+    }
+
+    #[test]
+    fn optimize_should_stop_at_the_generation_limit() {
+        let formula = random_3_sat_formula(100, 430, 11);
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let outcome = optimize(&formula, &parameters(10, 20), &mut rng);
+
+        assert_eq!(outcome.termination, Termination::GenerationLimit);
+        assert_eq!(outcome.fitness_history.len(), 20);
+        assert_eq!(outcome.evaluations, 10 * 20);
+    }
+
+    #[test]
+    fn optimize_should_stop_at_the_evaluation_limit() {
+        let formula = random_3_sat_formula(100, 430, 12);
+        let mut rng = StdRng::seed_from_u64(12);
+        let parameters = Parameters {
+            maximum_number_of_evaluations: Some(95),
+            ..parameters(10, 1_000)
+        };
+
+        let outcome = optimize(&formula, &parameters, &mut rng);
+
+        assert_eq!(outcome.termination, Termination::EvaluationLimit);
+        assert_eq!(outcome.evaluations, 100);
+    }
+
+    #[test]
+    fn optimize_should_keep_a_best_individual_found_by_the_last_generation() {
+        let formula = random_3_sat_formula(100, 430, 16);
+        let parameters = Parameters {
+            elitism: 1,
+            ..parameters(10, 200)
+        };
+        let history =
+            optimize(&formula, &parameters, &mut StdRng::seed_from_u64(16)).fitness_history;
+        // The last generation improving on all the previous ones
+        let improving_generation = (1..history.len())
+            .rev()
+            .find(|&generation| {
+                history[..generation]
+                    .iter()
+                    .all(|&fitness| fitness < history[generation])
+            })
+            .expect("Some generation should improve on the initial population")
+            + 1;
+        let parameters = Parameters {
+            maximum_number_of_evaluations: Some(
+                u64::try_from(improving_generation).expect("The generation should fit in u64") * 10,
+            ),
+            ..parameters
+        };
+
+        let outcome = optimize(&formula, &parameters, &mut StdRng::seed_from_u64(16));
+
+        assert_eq!(outcome.termination, Termination::EvaluationLimit);
+        assert_eq!(outcome.fitness_history.len(), improving_generation);
+        assert!((outcome.best_fitness - history[improving_generation - 1]).abs() < f64::EPSILON);
+        assert!(
+            (outcome.best_individual.evaluate(&formula) - outcome.best_fitness).abs()
+                < f64::EPSILON
+        );
+    }
+
+    #[test]
+    fn optimize_should_satisfy_a_formula_of_only_tautologies() {
+        let parsed_formula = parse_dimacs::from_reader(
+            &b"p cnf 2 1\n1 -1 2 0\n"[..],
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The formula should be well formed");
+        assert_eq!(parsed_formula.formula.number_of_clauses(), 0);
+
+        for scheme in [
+            Scheme::Generational,
+            Scheme::SteadyState(Replacement::Worst),
+        ] {
+            let parameters = Parameters {
+                scheme,
+                ..parameters(10, 20)
+            };
+
+            let outcome = optimize(
+                &parsed_formula.formula,
+                &parameters,
+                &mut StdRng::seed_from_u64(17),
+            );
+
+            assert_eq!(outcome.termination, Termination::PerfectIndividual);
+            assert!((outcome.best_fitness - 1.).abs() < f64::EPSILON);
+        }
+    }
+
+    #[test]
+    fn optimize_should_stop_at_the_time_limit() {
+        let formula = random_3_sat_formula(100, 430, 13);
+        let mut rng = StdRng::seed_from_u64(13);
+        let parameters = Parameters {
+            time_limit: Some(Duration::ZERO),
+            ..parameters(10, 1_000)
+        };
+
+        let outcome = optimize(&formula, &parameters, &mut rng);
+
+        assert_eq!(outcome.termination, Termination::TimeLimit);
+        assert_eq!(outcome.fitness_history.len(), 1);
+    }
+
+    #[test]
+    fn optimize_should_stop_when_the_best_fitness_stagnates() {
+        let formula = random_3_sat_formula(100, 430, 14);
+        let mut rng = StdRng::seed_from_u64(14);
+        let parameters = Parameters {
+            mutation_probability: 0.,
+            stagnation: Some(5),
+            ..parameters(10, 100_000)
+        };
+
+        let outcome = optimize(&formula, &parameters, &mut rng);
+
+        assert_eq!(outcome.termination, Termination::Stagnation);
+        let best_fitness_history = &outcome.fitness_history;
+        let last_improvement = best_fitness_history
+            .iter()
+            .position(|&fitness| (fitness - outcome.best_fitness).abs() < f64::EPSILON)
+            .expect("The best fitness is in the history");
+        assert_eq!(best_fitness_history.len() - last_improvement, 6);
+    }
+
+    #[test]
+    fn optimize_should_stop_when_cancelled_from_another_thread() {
+        let mut formula = random_3_sat_formula(100, 430, 15);
+        // An unsatisfiable formula, so that only the cancellation can stop the search
+        formula.push_clause(&[Literal::new(0, true)]);
+        formula.push_clause(&[Literal::new(0, false)]);
+        let mut rng = StdRng::seed_from_u64(15);
+        let parameters = parameters(10, u32::MAX);
+
+        let outcome = std::thread::scope(|scope| {
+            let cancellation = parameters.cancellation.clone();
+            scope.spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                cancellation.cancel();
+            });
+            optimize(&formula, &parameters, &mut rng)
+        });
+
+        assert_eq!(outcome.termination, Termination::Cancelled);
+        // Every generation scored before the cancellation is recorded, the last one included
+        assert_eq!(
+            outcome.evaluations,
+            u64::try_from(outcome.fitness_history.len()).expect("The history should fit in u64")
+                * 10
+        );
+        let best_recorded_fitness = outcome
+            .fitness_history
+            .iter()
+            .fold(0_f64, |maximum, &fitness| maximum.max(fitness));
+        assert!((outcome.best_fitness - best_recorded_fitness).abs() < f64::EPSILON);
+        assert!(
+            (outcome.best_individual.evaluate(&formula) - outcome.best_fitness).abs()
+                < f64::EPSILON
+        );
+    }
+
+    #[test]
+    fn binary_crossover_should_return_the_requested_number_of_children() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let breeding_individuals = Population::generate_random_individuals(40, 5, &mut rng);
+
+        let embryos = Population::binary_crossover(
+            breeding_individuals,
+            17,
+            &Uniform,
+            &Formula::new(40),
+            &mut rng,
+        );
+
+        assert_eq!(embryos.len(), 17);
+    }
+
+    #[test]
+    fn mutation_should_mutate_embryos_with_the_mutation_probability() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let number_of_embryos = 10_000;
+        let mutation_probability = 0.3;
+        let embryos = Population::generate_random_individuals(50, number_of_embryos, &mut rng);
+        let formula = Formula::new(50);
+
+        let children = Population::mutation(
+            embryos.clone(),
+            mutation_probability,
+            &KFlip { flips: 1 },
+            &formula,
+            &Occurrences::new(&formula),
+            &mut rng,
+        );
+
+        let number_of_mutated_children = embryos
+            .iter()
+            .zip(&children)
+            .filter(|(embryo, child)| embryo != child)
+            .count();
+        let mutation_rate = f64::from(
+            u32::try_from(number_of_mutated_children)
+                .expect("There are less children than the maximum value of u32"),
+        ) / f64::from(number_of_embryos);
+        assert!((mutation_rate - f64::from(mutation_probability)).abs() < 0.02);
+    }
+
+    #[test]
+    fn coupling_should_only_give_children_alleles_from_their_parents() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let parents = Population::generate_random_individuals(150, 100, &mut rng);
+        let couples: Vec<(&Solution, &Solution)> = parents
+            .iter()
+            .step_by(2)
+            .zip(parents.iter().skip(1).step_by(2))
+            .collect();
+
+        let embryos = Population::coupling(couples.clone(), &Uniform, &Formula::new(150), &mut rng);
+
+        for ((first_parent, second_parent), embryo) in couples.iter().zip(&embryos) {
+            for variable in 0..embryo.len() {
+                assert!(
+                    embryo.get(variable) == first_parent.get(variable)
+                        || embryo.get(variable) == second_parent.get(variable)
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,261 @@
+use rand::seq::index;
+use rand::{Rng, RngExt};
+
+use crate::sat::{Formula, Solution};
+
+/// Breeds an embryo out of two parents
+pub trait Crossover: Sync {
+    fn cross(
+        &self,
+        first_parent: &Solution,
+        second_parent: &Solution,
+        formula: &Formula,
+        rng: &mut dyn Rng,
+    ) -> Solution;
+}
+
+/// Takes each literal from the first parent where the mask bit is set,
+/// and from the second parent otherwise
+fn merge(
+    first_parent: &Solution,
+    second_parent: &Solution,
+    masks: impl Iterator<Item = u64>,
+) -> Solution {
+    let embryo_words: Vec<u64> = first_parent
+        .words()
+        .iter()
+        .zip(second_parent.words())
+        .zip(masks)
+        .map(|((first_parent_word, second_parent_word), mask)| {
+            (first_parent_word & mask) | (second_parent_word & !mask)
+        })
+        .collect();
+    Solution::from_words(embryo_words, first_parent.len())
+}
+
+/// Each literal comes from either parent with the same probability
+pub struct Uniform;
+
+impl Crossover for Uniform {
+    fn cross(
+        &self,
+        first_parent: &Solution,
+        second_parent: &Solution,
+        _formula: &Formula,
+        rng: &mut dyn Rng,
+    ) -> Solution {
+        merge(
+            first_parent,
+            second_parent,
+            std::iter::repeat_with(|| rng.random()),
+        )
+    }
+}
+
+/// Each literal comes from the first parent with probability `first_parent_probability`
+pub struct BiasedUniform {
+    pub first_parent_probability: f64,
+}
+
+impl Crossover for BiasedUniform {
+    fn cross(
+        &self,
+        first_parent: &Solution,
+        second_parent: &Solution,
+        _formula: &Formula,
+        rng: &mut dyn Rng,
+    ) -> Solution {
+        let masks = std::iter::repeat_with(|| {
+            (0..u64::BITS).fold(0, |mask, bit| {
+                mask | (u64::from(rng.random_bool(self.first_parent_probability)) << bit)
+            })
+        });
+        merge(first_parent, second_parent, masks)
+    }
+}
+
+/// Cuts the parents at `points` random positions and alternates the segments
+/// taken from each parent, starting with the first one.
+/// One and two points crossovers are the special cases of 1 and 2 points
+pub struct KPoint {
+    pub points: usize,
+}
+
+impl Crossover for KPoint {
+    fn cross(
+        &self,
+        first_parent: &Solution,
+        second_parent: &Solution,
+        _formula: &Formula,
+        rng: &mut dyn Rng,
+    ) -> Solution {
+        let number_of_variables = first_parent.len();
+        if number_of_variables < 2 {
+            return first_parent.clone();
+        }
+        let number_of_cuts = self.points.min(number_of_variables - 1);
+        let cuts: Vec<usize> = index::sample(rng, number_of_variables - 1, number_of_cuts)
+            .into_iter()
+            .map(|cut| cut + 1)
+            .collect();
+
+        // Each cut switches parent for all the literals from the cut onward, so the mask
+        // of a word is the XOR of the switches of all cuts up to the end of the word
+        let masks = (0..first_parent.words().len()).map(|word_index| {
+            let first_bit = word_index * u64::BITS as usize;
+            !cuts.iter().fold(0, |mask, &cut| {
+                if cut <= first_bit {
+                    !mask
+                } else if cut < first_bit + u64::BITS as usize {
+                    mask ^ (u64::MAX << (cut - first_bit))
+                } else {
+                    mask
+                }
+            })
+        });
+        merge(first_parent, second_parent, masks)
+    }
+}
+
+/// For each clause satisfied by a single parent, the embryo inherits the variables
+/// of that clause from the parent satisfying it. The variables left once all clauses
+/// are considered come from either parent with the same probability
+pub struct ClauseAware;
+
+impl Crossover for ClauseAware {
+    fn cross(
+        &self,
+        first_parent: &Solution,
+        second_parent: &Solution,
+        formula: &Formula,
+        rng: &mut dyn Rng,
+    ) -> Solution {
+        let mut embryo = Uniform.cross(first_parent, second_parent, formula, rng);
+        let mut inherited_variables = Solution::new(first_parent.len());
+
+        for clause in formula.clauses() {
+            let satisfying_parent = match (
+                first_parent.satisfies_clause(clause),
+                second_parent.satisfies_clause(clause),
+            ) {
+                (true, false) => first_parent,
+                (false, true) => second_parent,
+                _ => continue,
+            };
+            for literal in clause {
+                let variable = literal.variable();
+                if !inherited_variables.get(variable) {
+                    embryo.set(variable, satisfying_parent.get(variable));
+                    inherited_variables.set(variable, true);
+                }
+            }
+        }
+
+        embryo
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sat::Literal;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn crossovers() -> Vec<Box<dyn Crossover>> {
+        vec![
+            Box::new(Uniform),
+            Box::new(BiasedUniform {
+                first_parent_probability: 0.8,
+            }),
+            Box::new(KPoint { points: 1 }),
+            Box::new(KPoint { points: 2 }),
+            Box::new(KPoint { points: 7 }),
+            Box::new(ClauseAware),
+        ]
+    }
+
+    fn random_3_sat_formula(number_of_variables: usize, rng: &mut StdRng) -> Formula {
+        let mut formula = Formula::new(number_of_variables);
+        for _ in 0..4 * number_of_variables {
+            let clause: Vec<Literal> = (0..3)
+                .map(|_| Literal::new(rng.random_range(0..number_of_variables), rng.random()))
+                .collect();
+            formula.push_clause(&clause);
+        }
+        formula
+    }
+
+    #[test]
+    fn every_crossover_only_gives_embryos_alleles_from_their_parents() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for number_of_variables in [1, 2, 63, 64, 65, 200] {
+            let formula = random_3_sat_formula(number_of_variables, &mut rng);
+            for crossover in crossovers() {
+                for _ in 0..20 {
+                    let first_parent = Solution::random(number_of_variables, &mut rng);
+                    let second_parent = Solution::random(number_of_variables, &mut rng);
+
+                    let embryo = crossover.cross(&first_parent, &second_parent, &formula, &mut rng);
+
+                    assert_eq!(embryo.len(), number_of_variables);
+                    for variable in 0..number_of_variables {
+                        assert!(
+                            embryo.get(variable) == first_parent.get(variable)
+                                || embryo.get(variable) == second_parent.get(variable)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn k_point_crossover_switches_parent_at_most_k_times() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let formula = Formula::new(150);
+        let first_parent = Solution::from_words(vec![u64::MAX; 3], 150);
+        let second_parent = Solution::new(150);
+
+        for points in [1, 2, 5] {
+            let embryo = KPoint { points }.cross(&first_parent, &second_parent, &formula, &mut rng);
+
+            let values: Vec<bool> = embryo.iter().collect();
+            let number_of_switches = values.windows(2).filter(|pair| pair[0] != pair[1]).count();
+            assert!(values[0]);
+            assert!(number_of_switches <= points);
+        }
+    }
+
+    #[test]
+    fn biased_uniform_crossover_favours_the_first_parent() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let formula = Formula::new(10_000);
+        let first_parent = Solution::from_words(vec![u64::MAX; 157], 10_000);
+        let second_parent = Solution::new(10_000);
+
+        let embryo = BiasedUniform {
+            first_parent_probability: 0.8,
+        }
+        .cross(&first_parent, &second_parent, &formula, &mut rng);
+
+        let inherited_from_first_parent = embryo.iter().filter(|value| *value).count();
+        assert!((7_700..8_300).contains(&inherited_from_first_parent));
+    }
+
+    #[test]
+    fn clause_aware_crossover_keeps_clauses_satisfied_by_a_single_parent() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut formula = Formula::new(4);
+        formula.push_clause(&[Literal::new(0, true), Literal::new(1, true)]);
+        formula.push_clause(&[Literal::new(2, false), Literal::new(3, false)]);
+        let first_parent = Solution::from([true, false, true, true]);
+        let second_parent = Solution::from([false, false, false, true]);
+
+        for _ in 0..10 {
+            let embryo = ClauseAware.cross(&first_parent, &second_parent, &formula, &mut rng);
+
+            assert_eq!(embryo, Solution::from([true, false, false, true]));
+        }
+    }
+}
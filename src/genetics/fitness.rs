@@ -0,0 +1,114 @@
+use crate::sat::{Formula, Solution};
+
+/// How individuals are scored when they compete to breed and to survive
+#[derive(Clone, Copy, Debug)]
+pub enum FitnessFunction {
+    /// The ratio of clauses an individual satisfies
+    SatisfiedRatio,
+    /// Stepwise adaptation of weights: every `interval` generations, the weight of each clause
+    /// the best individual does not satisfy grows by `increment`. The fitness of an individual
+    /// is the share of the total weight held by the clauses it satisfies
+    SteppedAdaptationOfWeights { interval: u32, increment: f64 },
+}
+
+/// The weight of each clause of a formula, all clauses weighing 1 at first
+#[derive(Clone, Debug)]
+pub struct ClauseWeights {
+    weights: Vec<f64>,
+    total_weight: f64,
+}
+
+impl ClauseWeights {
+    pub fn uniform(formula: &Formula) -> Self {
+        let weights = vec![1.; formula.number_of_clauses()];
+        let total_weight = weights.iter().sum();
+        ClauseWeights {
+            weights,
+            total_weight,
+        }
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// The weighted fitness of `solution`, along with the ratio of clauses it satisfies
+    /// as computed by `Solution::evaluate`. Both are 1 for a formula without clauses
+    pub fn evaluate(&self, solution: &Solution, formula: &Formula) -> (f64, f64) {
+        if formula.number_of_clauses() == 0 {
+            return (1., 1.);
+        }
+        let mut satisfied_weight = 0.;
+        let mut number_of_satisfied_clauses = 0_u32;
+        for (clause, weight) in formula.clauses().zip(&self.weights) {
+            if solution.satisfies_clause(clause) {
+                satisfied_weight += weight;
+                number_of_satisfied_clauses += 1;
+            }
+        }
+
+        let number_of_clauses = f64::from(
+            u32::try_from(formula.number_of_clauses())
+                .expect("The number of clauses should be less than the maximum value of u32"),
+        );
+        (
+            satisfied_weight / self.total_weight,
+            f64::from(number_of_satisfied_clauses) / number_of_clauses,
+        )
+    }
+
+    /// Adds `increment` to the weight of every clause `solution` does not satisfy
+    pub fn increase_unsatisfied(&mut self, solution: &Solution, formula: &Formula, increment: f64) {
+        for (clause, weight) in formula.clauses().zip(&mut self.weights) {
+            if !solution.satisfies_clause(clause) {
+                *weight += increment;
+                self.total_weight += increment;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sat::Literal;
+
+    fn formula() -> Formula {
+        let mut formula = Formula::new(2);
+        formula.push_clause(&[Literal::new(0, true)]);
+        formula.push_clause(&[Literal::new(1, true)]);
+        formula.push_clause(&[Literal::new(0, false), Literal::new(1, false)]);
+        formula
+    }
+
+    #[test]
+    fn uniform_weights_evaluate_like_the_satisfied_ratio() {
+        let formula = formula();
+        let weights = ClauseWeights::uniform(&formula);
+
+        for solution in [
+            Solution::from([false, false]),
+            Solution::from([true, false]),
+            Solution::from([true, true]),
+        ] {
+            let (weighted_fitness, satisfied_ratio) = weights.evaluate(&solution, &formula);
+
+            assert!((weighted_fitness - solution.evaluate(&formula)).abs() < f64::EPSILON);
+            assert!((satisfied_ratio - solution.evaluate(&formula)).abs() < f64::EPSILON);
+        }
+    }
+
+    #[test]
+    fn increase_unsatisfied_should_only_weigh_the_unsatisfied_clauses_more() {
+        let formula = formula();
+        let mut weights = ClauseWeights::uniform(&formula);
+
+        weights.increase_unsatisfied(&Solution::from([true, false]), &formula, 2.);
+
+        assert_eq!(weights.weights(), &[1., 3., 1.]);
+        let (weighted_fitness, satisfied_ratio) =
+            weights.evaluate(&Solution::from([false, true]), &formula);
+        assert!((weighted_fitness - 4. / 5.).abs() < f64::EPSILON);
+        assert!((satisfied_ratio - 2. / 3.).abs() < f64::EPSILON);
+    }
+}
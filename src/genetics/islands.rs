@@ -0,0 +1,395 @@
+use rand::rngs::StdRng;
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+
+use super::{Outcome, Parameters, Population, Search, Termination};
+use crate::evaluator::Occurrences;
+use crate::sat::{Formula, Solution};
+
+/// Which islands the migrants of each island go to
+#[derive(Clone, Copy, Debug)]
+pub enum Topology {
+    /// Each island sends its migrants to the next one, the last island to the first
+    Ring,
+    /// Each island sends its migrants to every other island
+    FullyConnected,
+    /// Each island sends its migrants to another island drawn at random at each migration
+    Random,
+}
+
+impl Topology {
+    /// The islands receiving the migrants of the island `source`, out of `number_of_islands`
+    fn destinations(
+        self,
+        source: usize,
+        number_of_islands: usize,
+        rng: &mut dyn Rng,
+    ) -> Vec<usize> {
+        if number_of_islands < 2 {
+            return Vec::new();
+        }
+        match self {
+            Topology::Ring => vec![(source + 1) % number_of_islands],
+            Topology::FullyConnected => (0..number_of_islands)
+                .filter(|&destination| destination != source)
+                .collect(),
+            Topology::Random => {
+                // Drawing among the other islands, then skipping over the source
+                let destination = rng.random_range(0..number_of_islands - 1);
+                vec![if destination >= source {
+                    destination + 1
+                } else {
+                    destination
+                }]
+            }
+        }
+    }
+}
+
+/// The settings of an island model run, where each island is a population
+/// with its own parameters
+pub struct Archipelago {
+    pub islands: Vec<Parameters>,
+    /// The number of generations between two migrations
+    pub migration_interval: u32,
+    /// The number of fittest individuals each island sends at each migration
+    pub number_of_migrants: u32,
+    pub topology: Topology,
+}
+
+/// The result of an island model run
+#[derive(Debug)]
+pub struct ArchipelagoOutcome {
+    /// The outcome of the island that found the fittest individual, with the best fitness
+    /// of each generation over all islands as history, and the flips and evaluations
+    /// of all islands
+    pub overall: Outcome,
+    /// The outcome of each island, in order
+    pub islands: Vec<Outcome>,
+}
+
+impl Search {
+    /// The `number_of_migrants` fittest individuals of the population
+    fn emigrants(&self, number_of_migrants: u32) -> Vec<Solution> {
+        self.population
+            .elite(&self.population_fitness, number_of_migrants)
+    }
+
+    /// Each immigrant takes the place of the least fit individual of the population
+    fn welcome(&mut self, immigrants: Vec<Solution>, formula: &Formula) {
+        self.evaluations += immigrants.len() as u64;
+        for immigrant in immigrants {
+            let least_fit_index = (0..self.population_fitness.len())
+                .min_by(|&first, &second| {
+                    self.population_fitness[first].total_cmp(&self.population_fitness[second])
+                })
+                .expect("The population should not be empty");
+            let (fitness, satisfied_ratio) = self.weights.evaluate(&immigrant, formula);
+            self.population.individuals[least_fit_index] = immigrant;
+            self.population_fitness[least_fit_index] = fitness;
+            self.population.satisfied_ratios[least_fit_index] = satisfied_ratio;
+        }
+        self.population.best_fitness = Population::best_fitness(&self.population.satisfied_ratios);
+    }
+}
+
+/// Runs one genetic algorithm per island, the islands evolving in parallel on the current
+/// rayon thread pool and exchanging their fittest individuals every `migration_interval`
+/// generations. Each island draws from its own generator seeded from `rng`, so that a run
+/// can be reproduced from the seed of the generator whatever the number of threads.
+/// The run stops as soon as an island finds an individual satisfying the formula
+///
+/// # Panics
+///
+/// Panics if there are no islands
+pub fn optimize(
+    formula: &Formula,
+    archipelago: &Archipelago,
+    rng: &mut dyn Rng,
+) -> ArchipelagoOutcome {
+    assert!(
+        !archipelago.islands.is_empty(),
+        "An archipelago should have at least one island"
+    );
+    let occurrences = Occurrences::new(formula);
+    let mut islands: Vec<(Search, StdRng, &Parameters)> = archipelago
+        .islands
+        .iter()
+        .enumerate()
+        .map(|(island_index, parameters)| {
+            let mut island_rng = StdRng::seed_from_u64(rng.random());
+            let search = Search::new(
+                formula,
+                parameters,
+                &format!("Island {island_index}: "),
+                &mut island_rng,
+            );
+            (search, island_rng, parameters)
+        })
+        .collect();
+
+    let is_over = |islands: &[(Search, StdRng, &Parameters)]| {
+        islands
+            .iter()
+            .any(|(search, _, _)| search.perfect_individual_found)
+            || islands
+                .iter()
+                .all(|(search, _, parameters)| search.is_over(parameters))
+    };
+    while !is_over(&islands) {
+        islands
+            .par_iter_mut()
+            .for_each(|(search, island_rng, parameters)| {
+                for _ in 0..archipelago.migration_interval.max(1) {
+                    search.advance(formula, &occurrences, parameters, island_rng);
+                    if search.is_over(parameters) {
+                        break;
+                    }
+                }
+            });
+        if is_over(&islands) {
+            break;
+        }
+
+        let emigrants: Vec<Vec<Solution>> = islands
+            .iter()
+            .map(|(search, _, _)| search.emigrants(archipelago.number_of_migrants))
+            .collect();
+        let mut immigrants: Vec<Vec<Solution>> = vec![Vec::new(); islands.len()];
+        for (source, migrants) in emigrants.into_iter().enumerate() {
+            for destination in archipelago
+                .topology
+                .destinations(source, islands.len(), rng)
+            {
+                immigrants[destination].extend(migrants.iter().cloned());
+            }
+        }
+        for ((search, _, _), island_immigrants) in islands.iter_mut().zip(immigrants) {
+            search.welcome(island_immigrants, formula);
+        }
+    }
+
+    let islands: Vec<Outcome> = islands
+        .into_iter()
+        .map(|(search, _, parameters)| {
+            let termination = search
+                .termination(parameters)
+                .unwrap_or(Termination::PerfectIndividualOnAnotherIsland);
+            search.into_outcome(termination)
+        })
+        .collect();
+    ArchipelagoOutcome::new(islands)
+}
+
+impl ArchipelagoOutcome {
+    fn new(islands: Vec<Outcome>) -> Self {
+        let number_of_generations = islands
+            .iter()
+            .map(|outcome| outcome.fitness_history.len())
+            .max()
+            .unwrap_or(0);
+        let fitness_history = (0..number_of_generations)
+            .map(|generation| {
+                islands
+                    .iter()
+                    .filter_map(|outcome| outcome.fitness_history.get(generation))
+                    .fold(0_f64, |maximum, &fitness| maximum.max(fitness))
+            })
+            .collect();
+        let best_island = islands
+            .iter()
+            .reduce(|best, outcome| {
+                if outcome.best_fitness > best.best_fitness {
+                    outcome
+                } else {
+                    best
+                }
+            })
+            .expect("There is at least one island");
+
+        ArchipelagoOutcome {
+            overall: Outcome {
+                best_individual: best_island.best_individual.clone(),
+                best_fitness: best_island.best_fitness,
+                fitness_history,
+                flips: islands.iter().map(|outcome| outcome.flips).sum(),
+                evaluations: islands.iter().map(|outcome| outcome.evaluations).sum(),
+                termination: best_island.termination,
+                clause_weights: best_island.clause_weights.clone(),
+            },
+            islands,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cancellation::Cancellation;
+    use crate::genetics::Scheme;
+    use crate::genetics::crossover::Uniform;
+    use crate::genetics::fitness::FitnessFunction;
+    use crate::genetics::mutation::KFlip;
+    use crate::genetics::selection::Tournament;
+    use crate::sat::Literal;
+
+    fn random_3_sat_formula(number_of_variables: usize, number_of_clauses: usize) -> Formula {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut formula = Formula::new(number_of_variables);
+        for _ in 0..number_of_clauses {
+            let clause: Vec<Literal> = (0..3)
+                .map(|_| Literal::new(rng.random_range(0..number_of_variables), rng.random()))
+                .collect();
+            formula.push_clause(&clause);
+        }
+        formula
+    }
+
+    fn parameters(mutation_probability: f32) -> Parameters {
+        Parameters {
+            population_size: 10,
+            maximum_number_of_generations: 30,
+            maximum_number_of_breeding_individuals: 6,
+            mutation_probability,
+            selection: Box::new(Tournament { size: 2 }),
+            crossover: Box::new(Uniform),
+            mutation: Box::new(KFlip { flips: 1 }),
+            elitism: 1,
+            scheme: Scheme::Generational,
+            local_search: None,
+            local_search_individuals: None,
+            fitness: FitnessFunction::SatisfiedRatio,
+            time_limit: None,
+            maximum_number_of_evaluations: None,
+            stagnation: None,
+            cancellation: Cancellation::default(),
+            message_prefix: "",
+            quiet: false,
+        }
+    }
+
+    fn archipelago(topology: Topology) -> Archipelago {
+        Archipelago {
+            islands: vec![parameters(0.1), parameters(0.5), parameters(0.9)],
+            migration_interval: 4,
+            number_of_migrants: 2,
+            topology,
+        }
+    }
+
+    #[test]
+    fn ring_topology_sends_migrants_to_the_next_island() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert_eq!(Topology::Ring.destinations(0, 3, &mut rng), vec![1]);
+        assert_eq!(Topology::Ring.destinations(2, 3, &mut rng), vec![0]);
+    }
+
+    #[test]
+    fn fully_connected_topology_sends_migrants_to_every_other_island() {
+        let mut rng = StdRng::seed_from_u64(2);
+
+        assert_eq!(
+            Topology::FullyConnected.destinations(1, 4, &mut rng),
+            vec![0, 2, 3]
+        );
+    }
+
+    #[test]
+    fn random_topology_never_sends_migrants_to_their_own_island() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for source in 0..4 {
+            for _ in 0..50 {
+                let destinations = Topology::Random.destinations(source, 4, &mut rng);
+
+                assert_eq!(destinations.len(), 1);
+                assert_ne!(destinations[0], source);
+                assert!(destinations[0] < 4);
+            }
+        }
+    }
+
+    #[test]
+    fn optimize_reports_every_island_and_the_best_of_them() {
+        let formula = random_3_sat_formula(50, 215);
+
+        for topology in [Topology::Ring, Topology::FullyConnected, Topology::Random] {
+            let outcome = optimize(
+                &formula,
+                &archipelago(topology),
+                &mut StdRng::seed_from_u64(4),
+            );
+
+            assert_eq!(outcome.islands.len(), 3);
+            let best_island_fitness = outcome
+                .islands
+                .iter()
+                .map(|island| island.best_fitness)
+                .fold(0_f64, f64::max);
+            assert!((outcome.overall.best_fitness - best_island_fitness).abs() < f64::EPSILON);
+            assert!(
+                (outcome.overall.best_individual.evaluate(&formula) - best_island_fitness).abs()
+                    < f64::EPSILON
+            );
+        }
+    }
+
+    #[test]
+    fn optimize_stops_every_island_once_cancelled() {
+        let cancellation = Cancellation::new();
+        let mut archipelago = archipelago(Topology::Ring);
+        for parameters in &mut archipelago.islands {
+            parameters.cancellation = cancellation.clone();
+        }
+        cancellation.cancel();
+
+        let outcome = optimize(
+            &random_3_sat_formula(50, 215),
+            &archipelago,
+            &mut StdRng::seed_from_u64(6),
+        );
+
+        assert_eq!(outcome.overall.termination, Termination::Cancelled);
+        for island in &outcome.islands {
+            assert_eq!(island.termination, Termination::Cancelled);
+            assert_eq!(island.fitness_history.len(), 1);
+        }
+    }
+
+    #[test]
+    fn optimize_does_not_depend_on_the_number_of_threads() {
+        let formula = random_3_sat_formula(50, 215);
+        let run_with_threads = |number_of_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(number_of_threads)
+                .build()
+                .expect("The thread pool should build")
+                .install(|| {
+                    optimize(
+                        &formula,
+                        &archipelago(Topology::Random),
+                        &mut StdRng::seed_from_u64(5),
+                    )
+                })
+        };
+
+        let single_threaded_outcome = run_with_threads(1);
+        let multi_threaded_outcome = run_with_threads(3);
+
+        assert_eq!(
+            single_threaded_outcome.overall.fitness_history,
+            multi_threaded_outcome.overall.fitness_history
+        );
+        for (single_threaded_island, multi_threaded_island) in single_threaded_outcome
+            .islands
+            .iter()
+            .zip(&multi_threaded_outcome.islands)
+        {
+            assert_eq!(
+                single_threaded_island.best_individual,
+                multi_threaded_island.best_individual
+            );
+        }
+    }
+}
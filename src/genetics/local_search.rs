@@ -0,0 +1,230 @@
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{Rng, RngExt};
+
+use crate::evaluator::{IncrementalEvaluator, Occurrences};
+use crate::sat::{Formula, Solution};
+
+/// Improves an individual by flipping its variables one at a time, returning the
+/// improved individual and the number of flips it took.
+/// Local searches are shared between the threads improving the children
+pub trait LocalSearch: Sync {
+    fn improve(
+        &self,
+        individual: Solution,
+        formula: &Formula,
+        occurrences: &Occurrences,
+        rng: &mut dyn Rng,
+    ) -> (Solution, u64);
+}
+
+/// Repeatedly picks a random unsatisfied clause and flips one of its variables,
+/// a random one with probability `noise` and otherwise the one breaking the fewest clauses.
+/// A variable breaking no clause is always flipped. Stops after `maximum_number_of_flips`
+/// attempts or once every clause is satisfied
+pub struct WalkSat {
+    pub maximum_number_of_flips: u64,
+    pub noise: f64,
+}
+
+impl LocalSearch for WalkSat {
+    fn improve(
+        &self,
+        individual: Solution,
+        formula: &Formula,
+        occurrences: &Occurrences,
+        rng: &mut dyn Rng,
+    ) -> (Solution, u64) {
+        let mut evaluator = IncrementalEvaluator::new(formula, occurrences, individual);
+        let mut number_of_flips = 0;
+
+        for _ in 0..self.maximum_number_of_flips {
+            let Some(&clause_index) = evaluator.unsatisfied_clauses().choose(rng) else {
+                break;
+            };
+            let clause = formula.clause(clause_index);
+            let Some((least_breaking_variable, least_break_count)) = clause
+                .iter()
+                .map(|literal| {
+                    (
+                        literal.variable(),
+                        evaluator.break_count(literal.variable()),
+                    )
+                })
+                .min_by_key(|&(_, break_count)| break_count)
+            else {
+                // An empty clause cannot be satisfied by any flip
+                continue;
+            };
+
+            let variable = if least_break_count > 0 && rng.random_bool(self.noise) {
+                clause
+                    .choose(rng)
+                    .expect("The clause is not empty")
+                    .variable()
+            } else {
+                least_breaking_variable
+            };
+            evaluator.flip(variable);
+            number_of_flips += 1;
+        }
+
+        (evaluator.into_solution(), number_of_flips)
+    }
+}
+
+/// Scans the variables in a random order, flipping each one whose flip satisfies more
+/// clauses than it breaks, and starts a new scan until one makes no flip or
+/// `maximum_number_of_flips` flips are made
+pub struct Greedy {
+    pub maximum_number_of_flips: u64,
+}
+
+impl LocalSearch for Greedy {
+    fn improve(
+        &self,
+        individual: Solution,
+        formula: &Formula,
+        occurrences: &Occurrences,
+        rng: &mut dyn Rng,
+    ) -> (Solution, u64) {
+        let mut variables: Vec<usize> = (0..individual.len()).collect();
+        let mut evaluator = IncrementalEvaluator::new(formula, occurrences, individual);
+        let mut number_of_flips = 0;
+
+        'scans: loop {
+            let mut improved = false;
+            variables.shuffle(rng);
+            for &variable in &variables {
+                if number_of_flips >= self.maximum_number_of_flips {
+                    break 'scans;
+                }
+                if evaluator.make_count(variable) > evaluator.break_count(variable) {
+                    evaluator.flip(variable);
+                    number_of_flips += 1;
+                    improved = true;
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+
+        (evaluator.into_solution(), number_of_flips)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sat::Literal;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    /// A random 3-SAT formula satisfied by `planted_solution`
+    fn planted_3_sat_formula(
+        planted_solution: &Solution,
+        number_of_clauses: usize,
+        rng: &mut StdRng,
+    ) -> Formula {
+        let number_of_variables = planted_solution.len();
+        let mut formula = Formula::new(number_of_variables);
+        while formula.number_of_clauses() < number_of_clauses {
+            let clause: Vec<Literal> = (0..3)
+                .map(|_| Literal::new(rng.random_range(0..number_of_variables), rng.random()))
+                .collect();
+            if planted_solution.satisfies_clause(&clause) {
+                formula.push_clause(&clause);
+            }
+        }
+        formula
+    }
+
+    fn local_searches() -> Vec<Box<dyn LocalSearch>> {
+        vec![
+            Box::new(WalkSat {
+                maximum_number_of_flips: 50,
+                noise: 0.5,
+            }),
+            Box::new(Greedy {
+                maximum_number_of_flips: 50,
+            }),
+        ]
+    }
+
+    #[test]
+    fn every_local_search_stays_within_its_flip_budget() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let planted_solution = Solution::random(100, &mut rng);
+        let formula = planted_3_sat_formula(&planted_solution, 400, &mut rng);
+        let occurrences = Occurrences::new(&formula);
+
+        for local_search in local_searches() {
+            for _ in 0..10 {
+                let individual = Solution::random(100, &mut rng);
+
+                let (_, number_of_flips) =
+                    local_search.improve(individual, &formula, &occurrences, &mut rng);
+
+                assert!(number_of_flips <= 50);
+            }
+        }
+    }
+
+    #[test]
+    fn every_local_search_leaves_a_satisfying_individual_unchanged() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let planted_solution = Solution::random(60, &mut rng);
+        let formula = planted_3_sat_formula(&planted_solution, 250, &mut rng);
+        let occurrences = Occurrences::new(&formula);
+
+        for local_search in local_searches() {
+            let (improved_individual, number_of_flips) =
+                local_search.improve(planted_solution.clone(), &formula, &occurrences, &mut rng);
+
+            assert_eq!(improved_individual, planted_solution);
+            assert_eq!(number_of_flips, 0);
+        }
+    }
+
+    #[test]
+    fn greedy_local_search_never_lowers_the_fitness() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let planted_solution = Solution::random(80, &mut rng);
+        let formula = planted_3_sat_formula(&planted_solution, 340, &mut rng);
+        let occurrences = Occurrences::new(&formula);
+        let greedy = Greedy {
+            maximum_number_of_flips: 1_000,
+        };
+
+        for _ in 0..10 {
+            let individual = Solution::random(80, &mut rng);
+            let fitness = individual.evaluate(&formula);
+
+            let (improved_individual, _) =
+                greedy.improve(individual, &formula, &occurrences, &mut rng);
+
+            assert!(improved_individual.evaluate(&formula) >= fitness);
+        }
+    }
+
+    #[test]
+    fn walksat_solves_an_easy_satisfiable_formula() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let planted_solution = Solution::random(50, &mut rng);
+        let formula = planted_3_sat_formula(&planted_solution, 150, &mut rng);
+        let occurrences = Occurrences::new(&formula);
+        let walksat = WalkSat {
+            maximum_number_of_flips: 10_000,
+            noise: 0.5,
+        };
+
+        let (improved_individual, _) = walksat.improve(
+            Solution::random(50, &mut rng),
+            &formula,
+            &occurrences,
+            &mut rng,
+        );
+
+        assert!((improved_individual.evaluate(&formula) - 1.).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,187 @@
+use rand::seq::{IndexedRandom, index};
+use rand::{Rng, RngExt};
+
+use crate::evaluator::{IncrementalEvaluator, Occurrences};
+use crate::sat::{Formula, Solution};
+
+/// Randomly alters an embryo
+pub trait Mutation: Sync {
+    fn mutate(
+        &self,
+        embryo: Solution,
+        formula: &Formula,
+        occurrences: &Occurrences,
+        rng: &mut dyn Rng,
+    ) -> Solution;
+}
+
+/// Flips each variable independently with probability `rate`,
+/// or with probability 1/n for n variables when no rate is given
+pub struct BitFlip {
+    pub rate: Option<f64>,
+}
+
+impl Mutation for BitFlip {
+    fn mutate(
+        &self,
+        mut embryo: Solution,
+        _formula: &Formula,
+        _occurrences: &Occurrences,
+        rng: &mut dyn Rng,
+    ) -> Solution {
+        if embryo.is_empty() {
+            return embryo;
+        }
+        let rate = self.rate.unwrap_or_else(|| {
+            1. / f64::from(
+                u32::try_from(embryo.len())
+                    .expect("The number of variables should be less than the maximum value of u32"),
+            )
+        });
+        for variable in 0..embryo.len() {
+            if rng.random_bool(rate) {
+                embryo.flip(variable);
+            }
+        }
+        embryo
+    }
+}
+
+/// Flips `flips` distinct variables drawn uniformly at random
+pub struct KFlip {
+    pub flips: usize,
+}
+
+impl Mutation for KFlip {
+    fn mutate(
+        &self,
+        mut embryo: Solution,
+        _formula: &Formula,
+        _occurrences: &Occurrences,
+        rng: &mut dyn Rng,
+    ) -> Solution {
+        let number_of_flips = self.flips.min(embryo.len());
+        for variable in index::sample(rng, embryo.len(), number_of_flips) {
+            embryo.flip(variable);
+        }
+        embryo
+    }
+}
+
+/// Flips `flips` times a random variable of a random clause the embryo does not satisfy,
+/// stopping early once the embryo satisfies every clause. The unsatisfied clauses are
+/// updated after each flip rather than found again by scanning the formula
+pub struct Focused {
+    pub flips: usize,
+}
+
+impl Mutation for Focused {
+    fn mutate(
+        &self,
+        embryo: Solution,
+        formula: &Formula,
+        occurrences: &Occurrences,
+        rng: &mut dyn Rng,
+    ) -> Solution {
+        let mut evaluator = IncrementalEvaluator::new(formula, occurrences, embryo);
+        for _ in 0..self.flips {
+            let Some(&unsatisfied_clause_index) = evaluator.unsatisfied_clauses().choose(rng)
+            else {
+                break;
+            };
+            // Empty clauses cannot be satisfied, so they have no variable worth flipping
+            if let Some(literal) = formula.clause(unsatisfied_clause_index).choose(rng) {
+                evaluator.flip(literal.variable());
+            }
+        }
+        evaluator.into_solution()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sat::Literal;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn bit_flip_without_a_rate_flips_one_variable_on_average() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let formula = Formula::new(100);
+        let embryo = Solution::random(100, &mut rng);
+        let occurrences = Occurrences::new(&formula);
+
+        let number_of_flips: usize = (0..10_000)
+            .map(|_| {
+                BitFlip { rate: None }
+                    .mutate(embryo.clone(), &formula, &occurrences, &mut rng)
+                    .hamming_distance(&embryo)
+            })
+            .sum();
+
+        assert!((9_500..10_500).contains(&number_of_flips));
+    }
+
+    #[test]
+    fn bit_flip_flips_variables_with_the_given_rate() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let formula = Formula::new(10_000);
+        let embryo = Solution::new(10_000);
+        let occurrences = Occurrences::new(&formula);
+
+        let mutated_embryo =
+            BitFlip { rate: Some(0.3) }.mutate(embryo.clone(), &formula, &occurrences, &mut rng);
+
+        assert!((2_800..3_200).contains(&mutated_embryo.hamming_distance(&embryo)));
+    }
+
+    #[test]
+    fn k_flip_flips_exactly_k_variables() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let formula = Formula::new(130);
+        let embryo = Solution::random(130, &mut rng);
+        let occurrences = Occurrences::new(&formula);
+
+        for flips in [1, 5, 64, 130] {
+            let mutated_embryo =
+                KFlip { flips }.mutate(embryo.clone(), &formula, &occurrences, &mut rng);
+
+            assert_eq!(mutated_embryo.hamming_distance(&embryo), flips);
+        }
+    }
+
+    #[test]
+    fn focused_mutation_only_flips_variables_of_unsatisfied_clauses() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut formula = Formula::new(4);
+        formula.push_clause(&[Literal::new(0, true), Literal::new(1, true)]);
+        formula.push_clause(&[Literal::new(2, true), Literal::new(3, true)]);
+        let embryo = Solution::from([false, false, true, false]);
+        let occurrences = Occurrences::new(&formula);
+
+        for _ in 0..20 {
+            let mutated_embryo =
+                Focused { flips: 1 }.mutate(embryo.clone(), &formula, &occurrences, &mut rng);
+
+            assert_eq!(mutated_embryo.hamming_distance(&embryo), 1);
+            assert!(mutated_embryo.get(0) || mutated_embryo.get(1));
+            assert_eq!(mutated_embryo.get(2), embryo.get(2));
+            assert_eq!(mutated_embryo.get(3), embryo.get(3));
+        }
+    }
+
+    #[test]
+    fn focused_mutation_leaves_a_satisfying_embryo_unchanged() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut formula = Formula::new(2);
+        formula.push_clause(&[Literal::new(0, true), Literal::new(1, false)]);
+        let embryo = Solution::from([true, true]);
+        let occurrences = Occurrences::new(&formula);
+
+        let mutated_embryo =
+            Focused { flips: 3 }.mutate(embryo.clone(), &formula, &occurrences, &mut rng);
+
+        assert_eq!(mutated_embryo, embryo);
+    }
+}
@@ -0,0 +1,323 @@
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use rand::seq::IndexedRandom;
+use rand::{Rng, RngExt};
+
+use crate::sat::Solution;
+
+/// Chooses the individuals allowed to breed, given each individual paired with its fitness
+pub trait Selection: Sync {
+    fn select(
+        &self,
+        individual_fitness_map: &[(Solution, f64)],
+        number_of_breeding_individuals: u32,
+        rng: &mut dyn Rng,
+    ) -> Vec<Solution>;
+}
+
+/// Fitness proportional sampling without replacement, so at most every individual is selected once
+pub struct Roulette;
+
+impl Selection for Roulette {
+    fn select(
+        &self,
+        individual_fitness_map: &[(Solution, f64)],
+        number_of_breeding_individuals: u32,
+        rng: &mut dyn Rng,
+    ) -> Vec<Solution> {
+        individual_fitness_map
+            .sample_weighted(rng, number_of_breeding_individuals as usize, |item| {
+                item.1
+            })
+            .expect("The choice based on the fitness should not fail, as the weights should all be within the unit interval")
+            .map(|item| item.0.clone())
+            .collect()
+    }
+}
+
+/// Each breeding individual is the fittest of `size` individuals drawn uniformly at random
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl Selection for Tournament {
+    fn select(
+        &self,
+        individual_fitness_map: &[(Solution, f64)],
+        number_of_breeding_individuals: u32,
+        rng: &mut dyn Rng,
+    ) -> Vec<Solution> {
+        if individual_fitness_map.is_empty() {
+            return Vec::new();
+        }
+
+        (0..number_of_breeding_individuals)
+            .map(|_| {
+                let winner = (0..self.size.max(1))
+                    .map(|_| {
+                        individual_fitness_map
+                            .choose(rng)
+                            .expect("The population is not empty")
+                    })
+                    .max_by(|first, second| first.1.total_cmp(&second.1))
+                    .expect("A tournament has at least one contestant");
+                winner.0.clone()
+            })
+            .collect()
+    }
+}
+
+/// Sampling with replacement where the weight of an individual only depends on its rank.
+/// The fittest individual is `selection_pressure` times more likely to be picked than an
+/// average one, with `selection_pressure` between 1 (no pressure) and 2
+pub struct LinearRank {
+    pub selection_pressure: f64,
+}
+
+impl Selection for LinearRank {
+    fn select(
+        &self,
+        individual_fitness_map: &[(Solution, f64)],
+        number_of_breeding_individuals: u32,
+        rng: &mut dyn Rng,
+    ) -> Vec<Solution> {
+        if individual_fitness_map.is_empty() {
+            return Vec::new();
+        }
+
+        let ranked_individuals = sorted_by_decreasing_fitness(individual_fitness_map);
+        let last_rank = f64::from(
+            u32::try_from(ranked_individuals.len() - 1)
+                .expect("The population size should be less than the maximum value of u32"),
+        )
+        .max(1.);
+        let weights = (0..ranked_individuals.len()).map(|rank| {
+            let rank = f64::from(
+                u32::try_from(rank)
+                    .expect("The population size should be less than the maximum value of u32"),
+            );
+            self.selection_pressure - 2. * (self.selection_pressure - 1.) * rank / last_rank
+        });
+        // When the pressure is 2 the least fit individual has a weight of 0,
+        // with a single individual all weights would then be 0
+        let Ok(rank_distribution) = WeightedIndex::new(weights) else {
+            return vec![ranked_individuals[0].0.clone(); number_of_breeding_individuals as usize];
+        };
+
+        (0..number_of_breeding_individuals)
+            .map(|_| ranked_individuals[rank_distribution.sample(rng)].0.clone())
+            .collect()
+    }
+}
+
+/// Fitness proportional sampling with evenly spaced pointers over the cumulated fitness,
+/// which keeps the number of copies of each individual close to its expected value
+pub struct StochasticUniversalSampling;
+
+impl Selection for StochasticUniversalSampling {
+    fn select(
+        &self,
+        individual_fitness_map: &[(Solution, f64)],
+        number_of_breeding_individuals: u32,
+        rng: &mut dyn Rng,
+    ) -> Vec<Solution> {
+        let total_fitness: f64 = individual_fitness_map.iter().map(|item| item.1).sum();
+        if individual_fitness_map.is_empty() || number_of_breeding_individuals == 0 {
+            return Vec::new();
+        }
+
+        let distance_between_pointers = total_fitness / f64::from(number_of_breeding_individuals);
+        let mut pointer = rng.random::<f64>() * distance_between_pointers;
+        let mut cumulated_fitness = 0.;
+        let mut individuals = individual_fitness_map.iter();
+        let mut current_individual = None;
+        let mut breeding_individuals = Vec::with_capacity(number_of_breeding_individuals as usize);
+
+        for _ in 0..number_of_breeding_individuals {
+            while cumulated_fitness <= pointer {
+                match individuals.next() {
+                    Some(individual) => {
+                        cumulated_fitness += individual.1;
+                        current_individual = Some(individual);
+                    }
+                    // Rounding errors can leave the last pointer past the cumulated fitness
+                    None => break,
+                }
+            }
+            let (individual, _) =
+                current_individual.expect("The first pointer is within the first individual");
+            breeding_individuals.push(individual.clone());
+            pointer += distance_between_pointers;
+        }
+
+        breeding_individuals
+    }
+}
+
+/// Uniform sampling with replacement among the fittest `proportion` of the population
+pub struct Truncation {
+    pub proportion: f64,
+}
+
+impl Selection for Truncation {
+    fn select(
+        &self,
+        individual_fitness_map: &[(Solution, f64)],
+        number_of_breeding_individuals: u32,
+        rng: &mut dyn Rng,
+    ) -> Vec<Solution> {
+        if individual_fitness_map.is_empty() {
+            return Vec::new();
+        }
+
+        let ranked_individuals = sorted_by_decreasing_fitness(individual_fitness_map);
+        let as_f64 = |count: usize| {
+            f64::from(
+                u32::try_from(count)
+                    .expect("The population size should be less than the maximum value of u32"),
+            )
+        };
+        let kept_proportion_of_population = self.proportion * as_f64(ranked_individuals.len());
+        // The smallest number of individuals covering the proportion, at least one
+        let number_of_kept_individuals = (1..=ranked_individuals.len())
+            .find(|&count| as_f64(count) >= kept_proportion_of_population)
+            .unwrap_or(ranked_individuals.len());
+        let kept_individuals = &ranked_individuals[..number_of_kept_individuals];
+
+        (0..number_of_breeding_individuals)
+            .map(|_| {
+                kept_individuals
+                    .choose(rng)
+                    .expect("At least one individual is kept")
+                    .0
+                    .clone()
+            })
+            .collect()
+    }
+}
+
+fn sorted_by_decreasing_fitness(
+    individual_fitness_map: &[(Solution, f64)],
+) -> Vec<&(Solution, f64)> {
+    let mut sorted_individuals: Vec<&(Solution, f64)> = individual_fitness_map.iter().collect();
+    sorted_individuals.sort_by(|first, second| second.1.total_cmp(&first.1));
+    sorted_individuals
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn individual_fitness_map() -> Vec<(Solution, f64)> {
+        vec![
+            (Solution::from([false, false]), 0.1),
+            (Solution::from([false, true]), 0.2),
+            (Solution::from([true, false]), 0.3),
+            (Solution::from([true, true]), 0.4),
+        ]
+    }
+
+    fn count_copies(breeding_population: &[Solution], individual: &Solution) -> usize {
+        breeding_population
+            .iter()
+            .filter(|breeding_individual| *breeding_individual == individual)
+            .count()
+    }
+
+    #[test]
+    fn every_selection_returns_the_requested_number_of_individuals() {
+        let selections: Vec<Box<dyn Selection>> = vec![
+            Box::new(Tournament { size: 2 }),
+            Box::new(LinearRank {
+                selection_pressure: 1.5,
+            }),
+            Box::new(StochasticUniversalSampling),
+            Box::new(Truncation { proportion: 0.5 }),
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for selection in selections {
+            let breeding_population = selection.select(&individual_fitness_map(), 7, &mut rng);
+
+            assert_eq!(breeding_population.len(), 7);
+        }
+    }
+
+    #[test]
+    fn every_selection_returns_an_empty_vector_for_an_empty_population() {
+        let selections: Vec<Box<dyn Selection>> = vec![
+            Box::new(Roulette),
+            Box::new(Tournament { size: 2 }),
+            Box::new(LinearRank {
+                selection_pressure: 1.5,
+            }),
+            Box::new(StochasticUniversalSampling),
+            Box::new(Truncation { proportion: 0.5 }),
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for selection in selections {
+            assert!(selection.select(&[], 3, &mut rng).is_empty());
+        }
+    }
+
+    #[test]
+    fn tournament_as_large_as_the_population_mostly_selects_the_fittest_individual() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let fittest_individual = Solution::from([true, true]);
+
+        let breeding_population =
+            Tournament { size: 20 }.select(&individual_fitness_map(), 100, &mut rng);
+
+        assert!(count_copies(&breeding_population, &fittest_individual) > 95);
+    }
+
+    #[test]
+    fn linear_rank_with_the_highest_pressure_never_selects_the_least_fit_individual() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let least_fit_individual = Solution::from([false, false]);
+
+        let breeding_population = LinearRank {
+            selection_pressure: 2.,
+        }
+        .select(&individual_fitness_map(), 200, &mut rng);
+
+        assert_eq!(count_copies(&breeding_population, &least_fit_individual), 0);
+    }
+
+    #[test]
+    fn stochastic_universal_sampling_selects_individuals_proportionally_to_their_fitness() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let individual_fitness_map = individual_fitness_map();
+
+        let breeding_population =
+            StochasticUniversalSampling.select(&individual_fitness_map, 10, &mut rng);
+
+        for (individual, expected_copies) in individual_fitness_map
+            .iter()
+            .map(|item| &item.0)
+            .zip([1, 2, 3, 4])
+        {
+            assert_eq!(
+                count_copies(&breeding_population, individual),
+                expected_copies
+            );
+        }
+    }
+
+    #[test]
+    fn truncation_only_selects_the_fittest_proportion() {
+        let mut rng = StdRng::seed_from_u64(4);
+
+        let breeding_population =
+            Truncation { proportion: 0.5 }.select(&individual_fitness_map(), 50, &mut rng);
+
+        assert!(
+            breeding_population
+                .iter()
+                .all(|individual| individual.get(0))
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use rand::{Rng, RngExt};
+
+use super::fitness::ClauseWeights;
+use super::{Parameters, Population};
+use crate::evaluator::Occurrences;
+use crate::sat::{Formula, Solution};
+
+/// Which individual a new child takes the place of in the steady-state scheme
+#[derive(Clone, Copy, Debug)]
+pub enum Replacement {
+    /// The least fit individual of the population
+    Worst,
+    /// The least fit of `size` individuals drawn uniformly at random
+    TournamentLoser { size: usize },
+}
+
+impl Replacement {
+    fn victim(self, individual_fitness_map: &[(Solution, f64)], rng: &mut dyn Rng) -> usize {
+        let candidates: Vec<usize> = match self {
+            Replacement::Worst => (0..individual_fitness_map.len()).collect(),
+            Replacement::TournamentLoser { size } => (0..size.max(1))
+                .map(|_| rng.random_range(0..individual_fitness_map.len()))
+                .collect(),
+        };
+        candidates
+            .into_iter()
+            .min_by(|&first, &second| {
+                individual_fitness_map[first]
+                    .1
+                    .total_cmp(&individual_fitness_map[second].1)
+            })
+            .expect("The population should not be empty")
+    }
+}
+
+impl Population {
+    /// Breeds one child at a time, as many times as there are individuals, each child
+    /// replacing an individual chosen by `replacement` unless its genome is already
+    /// in the population
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn steady_state_generation(
+        self,
+        formula: &Formula,
+        occurrences: &Occurrences,
+        weights: &ClauseWeights,
+        population_fitness: Vec<f64>,
+        parameters: &Parameters,
+        replacement: Replacement,
+        rng: &mut dyn Rng,
+    ) -> (Population, Vec<f64>) {
+        let mut individual_fitness_map: Vec<(Solution, f64)> = self
+            .individuals
+            .into_iter()
+            .zip(population_fitness)
+            .collect();
+        let mut satisfied_ratios = self.satisfied_ratios;
+        let mut number_of_flips = 0;
+        let mut number_of_evaluations = 0;
+
+        for _ in 0..parameters.population_size {
+            // As with the generational scheme, individuals without fitness never breed
+            let parents = if individual_fitness_map
+                .iter()
+                .all(|&(_, fitness)| fitness > 0.)
+            {
+                parameters.selection.select(&individual_fitness_map, 2, rng)
+            } else {
+                let living_individuals: Vec<(Solution, f64)> = individual_fitness_map
+                    .iter()
+                    .filter(|&&(_, fitness)| fitness > 0.)
+                    .cloned()
+                    .collect();
+                parameters.selection.select(&living_individuals, 2, rng)
+            };
+            let [first_parent, second_parent] = parents.as_slice() else {
+                continue;
+            };
+
+            let mut child = parameters
+                .crossover
+                .cross(first_parent, second_parent, formula, rng);
+            if rng.random::<f32>() < parameters.mutation_probability {
+                child = parameters.mutation.mutate(child, formula, occurrences, rng);
+            }
+            if let Some(local_search) = &parameters.local_search {
+                let flips;
+                (child, flips) = local_search.improve(child, formula, occurrences, rng);
+                number_of_flips += flips;
+            }
+            if individual_fitness_map
+                .iter()
+                .any(|(individual, _)| *individual == child)
+            {
+                continue;
+            }
+
+            let victim = replacement.victim(&individual_fitness_map, rng);
+            let (child_fitness, child_satisfied_ratio) = weights.evaluate(&child, formula);
+            number_of_evaluations += 1;
+            individual_fitness_map[victim] = (child, child_fitness);
+            satisfied_ratios[victim] = child_satisfied_ratio;
+        }
+
+        let (individuals, population_fitness): (Vec<Solution>, Vec<f64>) =
+            individual_fitness_map.into_iter().unzip();
+        let mut population = Population::new(individuals);
+        population.best_fitness = Population::best_fitness(&satisfied_ratios);
+        population.satisfied_ratios = satisfied_ratios;
+        population.flips = number_of_flips;
+        population.evaluations = number_of_evaluations;
+        (population, population_fitness)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cancellation::Cancellation;
+    use crate::genetics::Scheme;
+    use crate::genetics::crossover::Uniform;
+    use crate::genetics::fitness::FitnessFunction;
+    use crate::genetics::mutation::KFlip;
+    use crate::genetics::selection::{Roulette, Tournament};
+    use crate::sat::Literal;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn parameters(replacement: Replacement) -> Parameters {
+        Parameters {
+            population_size: 20,
+            maximum_number_of_generations: 10,
+            maximum_number_of_breeding_individuals: 10,
+            mutation_probability: 0.5,
+            selection: Box::new(Tournament { size: 2 }),
+            crossover: Box::new(Uniform),
+            mutation: Box::new(KFlip { flips: 1 }),
+            elitism: 0,
+            scheme: Scheme::SteadyState(replacement),
+            local_search: None,
+            local_search_individuals: None,
+            fitness: FitnessFunction::SatisfiedRatio,
+            time_limit: None,
+            maximum_number_of_evaluations: None,
+            stagnation: None,
+            cancellation: Cancellation::default(),
+            message_prefix: "",
+            quiet: false,
+        }
+    }
+
+    fn formula() -> Formula {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut formula = Formula::new(40);
+        for _ in 0..170 {
+            let clause: Vec<Literal> = (0..3)
+                .map(|_| Literal::new(rng.random_range(0..40), rng.random()))
+                .collect();
+            formula.push_clause(&clause);
+        }
+        formula
+    }
+
+    #[test]
+    fn worst_replacement_picks_the_least_fit_individual() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let individual_fitness_map: Vec<(Solution, f64)> = [0.5, 0.2, 0.9, 0.3]
+            .into_iter()
+            .map(|fitness| (Solution::new(1), fitness))
+            .collect();
+
+        assert_eq!(
+            Replacement::Worst.victim(&individual_fitness_map, &mut rng),
+            1
+        );
+    }
+
+    #[test]
+    fn steady_state_generation_keeps_the_fitness_in_sync_and_the_genomes_unique() {
+        let formula = formula();
+        let occurrences = Occurrences::new(&formula);
+        let weights = ClauseWeights::uniform(&formula);
+        let mut rng = StdRng::seed_from_u64(2);
+
+        for replacement in [Replacement::Worst, Replacement::TournamentLoser { size: 3 }] {
+            let parameters = parameters(replacement);
+            let mut population = Population::genesis(40, 20, &mut rng);
+            let mut population_fitness = population.evaluate(&formula, &weights);
+
+            for _ in 0..10 {
+                (population, population_fitness) = population.steady_state_generation(
+                    &formula,
+                    &occurrences,
+                    &weights,
+                    population_fitness,
+                    &parameters,
+                    replacement,
+                    &mut rng,
+                );
+
+                assert_eq!(population.individuals.len(), 20);
+                for (individual, fitness) in population.individuals.iter().zip(&population_fitness)
+                {
+                    assert!((individual.evaluate(&formula) - fitness).abs() < f64::EPSILON);
+                }
+                for (index, individual) in population.individuals.iter().enumerate() {
+                    assert!(!population.individuals[index + 1..].contains(individual));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn steady_state_generation_never_breeds_individuals_without_fitness() {
+        let formula = formula();
+        let occurrences = Occurrences::new(&formula);
+        let weights = ClauseWeights::uniform(&formula);
+        let mut rng = StdRng::seed_from_u64(4);
+        let parameters = Parameters {
+            selection: Box::new(Roulette),
+            ..parameters(Replacement::Worst)
+        };
+        let population = Population::genesis(40, 20, &mut rng);
+        let individuals = population.individuals.clone();
+        let mut population_fitness = vec![0.; 20];
+        population_fitness[0] = 0.5;
+        population_fitness[1] = f64::NAN;
+
+        let (population, _) = population.steady_state_generation(
+            &formula,
+            &occurrences,
+            &weights,
+            population_fitness,
+            &parameters,
+            Replacement::Worst,
+            &mut rng,
+        );
+
+        // A single individual can breed, which takes two distinct parents with the roulette
+        assert_eq!(population.individuals, individuals);
+        assert_eq!(population.evaluations, 0);
+    }
+
+    #[test]
+    fn worst_replacement_never_loses_the_best_fitness() {
+        let formula = formula();
+        let occurrences = Occurrences::new(&formula);
+        let weights = ClauseWeights::uniform(&formula);
+        let mut rng = StdRng::seed_from_u64(3);
+        let parameters = parameters(Replacement::Worst);
+        let mut population = Population::genesis(40, 20, &mut rng);
+        let mut population_fitness = population.evaluate(&formula, &weights);
+
+        for _ in 0..20 {
+            let previous_best_fitness = population.best_fitness;
+            (population, population_fitness) = population.steady_state_generation(
+                &formula,
+                &occurrences,
+                &weights,
+                population_fitness,
+                &parameters,
+                Replacement::Worst,
+                &mut rng,
+            );
+
+            assert!(population.best_fitness >= previous_best_fitness);
+        }
+    }
+}
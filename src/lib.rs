@@ -1,21 +1,612 @@
+mod analysis;
+pub mod cancellation;
+mod competition;
+pub mod evaluator;
+mod generate;
 mod genetics;
+mod model;
 pub mod parse_arguments;
 mod parse_dimacs;
-mod sat;
+pub mod sat;
+mod write_dimacs;
 
-use parse_arguments::InputArguments;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
-pub fn run(args: &InputArguments) {
-    let formula = parse_dimacs::from_file(&args.formula_path);
-    let best_solution = genetics::optimize(
+use analysis::FormulaAnalysis;
+use cancellation::Cancellation;
+use competition::Status;
+use genetics::crossover::{BiasedUniform, ClauseAware, Crossover, KPoint, Uniform};
+use genetics::fitness::FitnessFunction;
+use genetics::islands::{self, Archipelago, Topology};
+use genetics::local_search::{Greedy, LocalSearch, WalkSat};
+use genetics::mutation::{BitFlip, Focused, KFlip, Mutation};
+use genetics::selection::{
+    LinearRank, Roulette, Selection, StochasticUniversalSampling, Tournament, Truncation,
+};
+use genetics::steady_state::Replacement;
+use genetics::{Outcome, Parameters, Scheme, Termination};
+use model::Model;
+use parse_arguments::{
+    BenchArguments, ConvertArguments, CrossoverOperator, FitnessMode, GenerateArguments,
+    LocalSearchAlgorithm, MigrationTopology, MutationOperator, OutputFormat, ReplacementScheme,
+    SearchArguments, SelectionStrategy, SolveArguments, StatsArguments, StatsFormat,
+    SteadyStateReplacement, VerifyArguments,
+};
+use parse_dimacs::{ParsedFormula, Simplification, Strictness};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use sat::{Formula, Solution};
+
+/// The exit code of a run stopped through its cancellation handle, as a shell reports
+/// a process interrupted by SIGINT
+pub const INTERRUPTED_EXIT_CODE: u8 = 130;
+
+/// Solves the formula described by `args`, printing the best solution found.
+/// Cancelling `cancellation` from another thread stops the search after the current
+/// generation, the best solution found so far is still printed and the run exits with
+/// `INTERRUPTED_EXIT_CODE`, unless it uses the competition output format
+#[must_use]
+pub fn solve(args: &SolveArguments, cancellation: &Cancellation) -> ExitCode {
+    let SolveArguments { search, output } = args;
+    let Some(parsed_formula) = read_formula(
+        &search.formula_path,
+        search.lenient,
+        Simplification::Simplify,
+    ) else {
+        return ExitCode::FAILURE;
+    };
+    let message_prefix = message_prefix(output.output_format);
+    let statistics = parsed_formula.statistics;
+    if statistics.tautological_clauses > 0 || statistics.duplicate_literals > 0 {
+        println!(
+            "{message_prefix}Dropped {} tautological clauses and {} duplicate literals",
+            statistics.tautological_clauses, statistics.duplicate_literals
+        );
+    }
+    let formula = parsed_formula.formula;
+
+    if !start_thread_pool(search.threads) {
+        return ExitCode::FAILURE;
+    }
+
+    let seed = search.seed.unwrap_or_else(rand::random);
+    println!("{message_prefix}Seed: {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (outcome, island_outcomes) = optimize(
         &formula,
-        args.population_size,
-        args.maximum_number_of_generations,
-        args.maximum_of_breeding_individuals_in_a_generation,
-        args.mutation_probability,
+        search,
+        cancellation,
+        message_prefix,
+        false,
+        &mut rng,
+    );
+    for (island_index, island_outcome) in island_outcomes.iter().enumerate() {
+        println!(
+            "{message_prefix}Island {island_index} ran for {} generations and reached a fitness of {}",
+            island_outcome.fitness_history.len(),
+            island_outcome.best_fitness
+        );
+        if search.local_search.is_some() {
+            println!(
+                "{message_prefix}Island {island_index} local search flipped {} variables",
+                island_outcome.flips
+            );
+        }
+    }
+
+    let best_solution = outcome.best_individual;
+    let best_fitness = outcome.best_fitness;
+    println!(
+        "{message_prefix}The search ran for {} generations",
+        outcome.fitness_history.len()
+    );
+    if output.output_format == OutputFormat::Human {
+        println!("The best solution is {best_solution:?}");
+    }
+    println!("{message_prefix}It has a fitness of {best_fitness}");
+    println!(
+        "{message_prefix}The search stopped because {} after {} evaluations",
+        outcome.termination, outcome.evaluations
+    );
+    if search.local_search.is_some() {
+        println!(
+            "{message_prefix}The local search flipped {} variables",
+            outcome.flips
+        );
+    }
+    if let Some(weights_path) = &output.dump_weights
+        && let Err(error) = write_clause_weights(weights_path, &formula, &outcome.clause_weights)
+    {
+        eprintln!(
+            "Error: could not write the clause weights to {}: {error}",
+            weights_path.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    if let Some(model_path) = &output.output_model
+        && let Err(error) = write_model(model_path, &formula, &best_solution)
+    {
+        eprintln!(
+            "Error: could not write the model to {}: {error}",
+            model_path.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    if output.output_format == OutputFormat::Competition {
+        return print_competition_result(&formula, &best_solution);
+    }
+    if outcome.termination == Termination::Cancelled {
+        return ExitCode::from(INTERRUPTED_EXIT_CODE);
+    }
+    ExitCode::SUCCESS
+}
+
+/// Solves the formula of `args` once per seed, starting from its seed, printing how each run
+/// went and a summary of all of them. Cancelling `cancellation` stops the current run and skips
+/// the remaining ones, the summary covering the runs made so far
+#[must_use]
+pub fn bench(args: &BenchArguments, cancellation: &Cancellation) -> ExitCode {
+    let search_arguments = &args.search;
+    let Some(parsed_formula) = read_formula(
+        &search_arguments.formula_path,
+        search_arguments.lenient,
+        Simplification::Simplify,
+    ) else {
+        return ExitCode::FAILURE;
+    };
+    let formula = parsed_formula.formula;
+    if !start_thread_pool(search_arguments.threads) {
+        return ExitCode::FAILURE;
+    }
+
+    let first_seed = search_arguments.seed.unwrap_or_else(rand::random);
+    let mut number_of_runs = 0;
+    let mut number_of_solved_runs = 0;
+    let mut total_fitness = 0.;
+    let mut total_evaluations = 0;
+    let mut total_time = Duration::ZERO;
+    for run_index in 0..args.runs {
+        if cancellation.is_cancelled() {
+            break;
+        }
+        let seed = first_seed.wrapping_add(u64::from(run_index));
+        let started = Instant::now();
+        let (outcome, _) = optimize(
+            &formula,
+            search_arguments,
+            cancellation,
+            "",
+            true,
+            &mut StdRng::seed_from_u64(seed),
+        );
+        let time = started.elapsed();
+        println!(
+            "Run {run_index} with seed {seed} reached a fitness of {} in {} generations, {} evaluations and {:.3}s, it stopped because {}",
+            outcome.best_fitness,
+            outcome.fitness_history.len(),
+            outcome.evaluations,
+            time.as_secs_f64(),
+            outcome.termination
+        );
+
+        number_of_runs += 1;
+        if Status::new(&formula, &outcome.best_individual) == Status::Satisfiable {
+            number_of_solved_runs += 1;
+        }
+        total_fitness += outcome.best_fitness;
+        total_evaluations += outcome.evaluations;
+        total_time += time;
+    }
+
+    if number_of_runs > 0 {
+        println!("{number_of_solved_runs} of the {number_of_runs} runs satisfied the formula");
+        println!(
+            "On average, a run reached a fitness of {} in {} evaluations and {:.3}s",
+            total_fitness / f64::from(number_of_runs),
+            total_evaluations / u64::from(number_of_runs),
+            (total_time / number_of_runs).as_secs_f64()
+        );
+    }
+    if cancellation.is_cancelled() {
+        return ExitCode::from(INTERRUPTED_EXIT_CODE);
+    }
+    ExitCode::SUCCESS
+}
+
+/// Prints the structure of the formula of `args` as written in its file,
+/// tautological clauses and duplicate literals included
+#[must_use]
+pub fn stats(args: &StatsArguments) -> ExitCode {
+    let Some(parsed_formula) = read_formula(
+        &args.formula.formula_path,
+        args.formula.lenient,
+        Simplification::Keep,
+    ) else {
+        return ExitCode::FAILURE;
+    };
+    let analysis = FormulaAnalysis::new(&parsed_formula.formula);
+
+    match args.format {
+        StatsFormat::Table => print!("{analysis}"),
+        StatsFormat::Json => match serde_json::to_string_pretty(&analysis) {
+            Ok(json) => println!("{json}"),
+            Err(error) => {
+                eprintln!("Error: could not format the statistics as JSON: {error}");
+                return ExitCode::FAILURE;
+            }
+        },
+    }
+    ExitCode::SUCCESS
+}
+
+/// Writes the random formula described by `args`, with its seed in a comment
+#[must_use]
+pub fn generate(args: &GenerateArguments) -> ExitCode {
+    let number_of_variables = args.variables as usize;
+    let clause_length = args.clause_length as usize;
+    if clause_length > number_of_variables {
+        eprintln!(
+            "Error: clauses of {clause_length} distinct variables cannot be drawn from {number_of_variables} variables"
+        );
+        return ExitCode::FAILURE;
+    }
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let planted_solution = args
+        .planted
+        .then(|| Solution::random(number_of_variables, &mut rng));
+    let formula = generate::random_k_sat(
+        number_of_variables,
+        args.clauses as usize,
+        clause_length,
+        planted_solution.as_ref(),
+        &mut rng,
     );
 
-    let best_fitness = best_solution.evaluate(&formula);
-    println!("The best solution is {best_solution:?}");
-    println!("It has a fitness of {best_fitness}");
+    let mut comments = vec![format!(
+        "Random {clause_length}-SAT formula generated by genetic_sat with seed {seed}"
+    )];
+    if args.planted {
+        comments.push(String::from("Satisfiable by a planted solution"));
+    }
+    if let Err(error) = write_dimacs::to_file(&args.output, &formula, &comments) {
+        eprintln!(
+            "Error: could not write the formula to {}: {error}",
+            args.output.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Writes the formula of `args` again, without the tautological clauses and duplicate
+/// literals dropped while parsing it
+#[must_use]
+pub fn convert(args: &ConvertArguments) -> ExitCode {
+    let Some(parsed_formula) = read_formula(
+        &args.formula.formula_path,
+        args.formula.lenient,
+        Simplification::Simplify,
+    ) else {
+        return ExitCode::FAILURE;
+    };
+    let statistics = parsed_formula.statistics;
+    if statistics.tautological_clauses > 0 || statistics.duplicate_literals > 0 {
+        // The formula may be written on the standard output
+        eprintln!(
+            "Dropped {} tautological clauses and {} duplicate literals",
+            statistics.tautological_clauses, statistics.duplicate_literals
+        );
+    }
+
+    if let Err(error) = write_dimacs::to_file(&args.output, &parsed_formula.formula, &[]) {
+        eprintln!(
+            "Error: could not write the formula to {}: {error}",
+            args.output.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Checks the model of `args` against its formula, printing every clause the model leaves
+/// unsatisfied, numbered from 1 in the order of the formula file, and the variables it leaves
+/// unassigned. The clauses are checked as written, tautologies included, so a partial model
+/// must still assign a variable of each of them.
+/// Succeeds when the model satisfies every clause, even if it is partial
+#[must_use]
+pub fn verify(args: &VerifyArguments) -> ExitCode {
+    let Some(parsed_formula) = read_formula(
+        &args.formula.formula_path,
+        args.formula.lenient,
+        Simplification::Keep,
+    ) else {
+        return ExitCode::FAILURE;
+    };
+    let model = match Model::from_file(&args.model_path) {
+        Ok(model) => model,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let formula = parsed_formula.formula;
+
+    let mut number_of_unsatisfied_clauses = 0;
+    for (clause_index, clause) in formula.clauses().enumerate() {
+        if !model.satisfies_clause(clause) {
+            number_of_unsatisfied_clauses += 1;
+            print!("Clause {} is not satisfied:", clause_index + 1);
+            for literal in clause {
+                print!(" {literal}");
+            }
+            println!(" 0");
+        }
+    }
+
+    let unassigned_variables = model.unassigned_variables(formula.number_of_variables);
+    if unassigned_variables.is_empty() {
+        println!("The model is complete");
+    } else {
+        print!(
+            "The model is partial, it leaves {} of the {} variables unassigned:",
+            unassigned_variables.len(),
+            formula.number_of_variables
+        );
+        for variable in unassigned_variables {
+            print!(" {}", variable + 1);
+        }
+        println!();
+    }
+
+    if number_of_unsatisfied_clauses == 0 {
+        println!("The model satisfies the formula");
+        ExitCode::SUCCESS
+    } else {
+        println!(
+            "The model leaves {number_of_unsatisfied_clauses} of the {} clauses unsatisfied",
+            formula.number_of_clauses()
+        );
+        ExitCode::FAILURE
+    }
+}
+
+/// Parses the formula at `formula_path`, printing its diagnostics as warnings,
+/// or printing the error that prevented parsing it
+fn read_formula(
+    formula_path: &Path,
+    lenient: bool,
+    simplification: Simplification,
+) -> Option<ParsedFormula> {
+    let strictness = if lenient {
+        Strictness::Lenient
+    } else {
+        Strictness::Strict
+    };
+    match parse_dimacs::from_file(formula_path, strictness, simplification) {
+        Ok(parsed_formula) => {
+            for diagnostic in &parsed_formula.diagnostics {
+                eprintln!("Warning: {diagnostic}");
+            }
+            Some(parsed_formula)
+        }
+        Err(error) => {
+            eprintln!("Error: {error}");
+            None
+        }
+    }
+}
+
+/// Sets the number of threads of the global rayon thread pool, returning whether it started
+fn start_thread_pool(number_of_threads: usize) -> bool {
+    if let Err(error) = rayon::ThreadPoolBuilder::new()
+        .num_threads(number_of_threads)
+        .build_global()
+    {
+        eprintln!("Error: could not start the thread pool: {error}");
+        return false;
+    }
+    true
+}
+
+/// Runs the genetic algorithm described by `args`, on islands when there is more than one,
+/// returning the overall outcome and the outcome of each island
+fn optimize(
+    formula: &Formula,
+    args: &SearchArguments,
+    cancellation: &Cancellation,
+    message_prefix: &'static str,
+    quiet: bool,
+    rng: &mut StdRng,
+) -> (Outcome, Vec<Outcome>) {
+    if args.islands > 1 {
+        let archipelago = archipelago(args, cancellation, message_prefix, quiet);
+        let archipelago_outcome = islands::optimize(formula, &archipelago, rng);
+        (archipelago_outcome.overall, archipelago_outcome.islands)
+    } else {
+        let parameters = parameters(args, cancellation, message_prefix, quiet);
+        let outcome = genetics::optimize(formula, &parameters, rng);
+        (outcome, Vec::new())
+    }
+}
+
+fn message_prefix(output_format: OutputFormat) -> &'static str {
+    match output_format {
+        OutputFormat::Human => "",
+        OutputFormat::Competition => "c ",
+    }
+}
+
+/// Prints the status line, followed by the model when the formula is satisfied,
+/// and returns the matching exit code
+fn print_competition_result(formula: &Formula, best_solution: &Solution) -> ExitCode {
+    let status = Status::new(formula, best_solution);
+    println!("s {status}");
+    if status == Status::Satisfiable
+        && let Err(error) = competition::write_model(&mut io::stdout().lock(), best_solution)
+    {
+        eprintln!("Error: could not write the model: {error}");
+        return ExitCode::FAILURE;
+    }
+    status.exit_code()
+}
+
+/// Writes the status of the best solution followed by its value lines
+fn write_model(path: &Path, formula: &Formula, best_solution: &Solution) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "s {}", Status::new(formula, best_solution))?;
+    competition::write_model(&mut writer, best_solution)?;
+    writer.flush()
+}
+
+/// Writes a line per clause made of its weight followed by its literals in the dimacs format
+fn write_clause_weights(path: &Path, formula: &Formula, weights: &[f64]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for (clause, weight) in formula.clauses().zip(weights) {
+        write!(writer, "{weight}")?;
+        for literal in clause {
+            write!(writer, " {literal}")?;
+        }
+        writeln!(writer, " 0")?;
+    }
+    writer.flush()
+}
+
+/// The islands share the parameters of the command line, but for their mutation probability
+/// when a list of probabilities is given
+fn archipelago(
+    args: &SearchArguments,
+    cancellation: &Cancellation,
+    message_prefix: &'static str,
+    quiet: bool,
+) -> Archipelago {
+    let islands = (0..args.islands as usize)
+        .map(|island_index| {
+            let island_parameters = parameters(args, cancellation, message_prefix, quiet);
+            if args.island_mutation_probabilities.is_empty() {
+                island_parameters
+            } else {
+                Parameters {
+                    mutation_probability: args.island_mutation_probabilities
+                        [island_index % args.island_mutation_probabilities.len()],
+                    ..island_parameters
+                }
+            }
+        })
+        .collect();
+    let topology = match args.topology {
+        MigrationTopology::Ring => Topology::Ring,
+        MigrationTopology::FullyConnected => Topology::FullyConnected,
+        MigrationTopology::Random => Topology::Random,
+    };
+
+    Archipelago {
+        islands,
+        migration_interval: args.migration_interval,
+        number_of_migrants: args.migrants,
+        topology,
+    }
+}
+
+fn parameters(
+    args: &SearchArguments,
+    cancellation: &Cancellation,
+    message_prefix: &'static str,
+    quiet: bool,
+) -> Parameters {
+    let selection: Box<dyn Selection> = match args.selection {
+        SelectionStrategy::Roulette => Box::new(Roulette),
+        SelectionStrategy::Tournament => Box::new(Tournament {
+            size: args.tournament_size as usize,
+        }),
+        SelectionStrategy::Rank => Box::new(LinearRank {
+            selection_pressure: args.selection_pressure,
+        }),
+        SelectionStrategy::Sus => Box::new(StochasticUniversalSampling),
+        SelectionStrategy::Truncation => Box::new(Truncation {
+            proportion: args.truncation_proportion,
+        }),
+    };
+
+    let crossover: Box<dyn Crossover> = match args.crossover {
+        CrossoverOperator::Uniform => Box::new(Uniform),
+        CrossoverOperator::BiasedUniform => Box::new(BiasedUniform {
+            first_parent_probability: args.crossover_bias,
+        }),
+        CrossoverOperator::OnePoint => Box::new(KPoint { points: 1 }),
+        CrossoverOperator::TwoPoint => Box::new(KPoint { points: 2 }),
+        CrossoverOperator::KPoint => Box::new(KPoint {
+            points: args.crossover_points as usize,
+        }),
+        CrossoverOperator::ClauseAware => Box::new(ClauseAware),
+    };
+
+    let mutation: Box<dyn Mutation> = match args.mutation {
+        MutationOperator::BitFlip => Box::new(BitFlip {
+            rate: args.mutation_rate,
+        }),
+        MutationOperator::KFlip => Box::new(KFlip {
+            flips: args.mutation_flips as usize,
+        }),
+        MutationOperator::Focused => Box::new(Focused {
+            flips: args.mutation_flips as usize,
+        }),
+    };
+
+    let scheme = match args.scheme {
+        ReplacementScheme::Generational => Scheme::Generational,
+        ReplacementScheme::SteadyState => Scheme::SteadyState(match args.replacement {
+            SteadyStateReplacement::Worst => Replacement::Worst,
+            SteadyStateReplacement::TournamentLoser => Replacement::TournamentLoser {
+                size: args.tournament_size as usize,
+            },
+        }),
+    };
+
+    let local_search = args.local_search.map(|algorithm| -> Box<dyn LocalSearch> {
+        match algorithm {
+            LocalSearchAlgorithm::Walksat => Box::new(WalkSat {
+                maximum_number_of_flips: args.local_search_flips,
+                noise: args.noise,
+            }),
+            LocalSearchAlgorithm::Greedy => Box::new(Greedy {
+                maximum_number_of_flips: args.local_search_flips,
+            }),
+        }
+    });
+
+    let fitness = match args.fitness {
+        FitnessMode::Ratio => FitnessFunction::SatisfiedRatio,
+        FitnessMode::Saw => FitnessFunction::SteppedAdaptationOfWeights {
+            interval: args.saw_interval,
+            increment: args.saw_increment,
+        },
+    };
+
+    Parameters {
+        population_size: args.population_size,
+        maximum_number_of_generations: args.maximum_number_of_generations,
+        maximum_number_of_breeding_individuals: args
+            .maximum_of_breeding_individuals_in_a_generation,
+        mutation_probability: args.mutation_probability,
+        selection,
+        crossover,
+        mutation,
+        elitism: args.elitism.min(args.population_size),
+        scheme,
+        local_search,
+        local_search_individuals: args.local_search_individuals,
+        fitness,
+        time_limit: args.time_limit,
+        maximum_number_of_evaluations: args.max_evaluations,
+        stagnation: args.stagnation,
+        cancellation: cancellation.clone(),
+        message_prefix,
+        quiet,
+    }
 }
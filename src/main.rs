@@ -1,7 +1,49 @@
+use std::process::{self, ExitCode};
+
 use clap::Parser;
-use genetic_sat::{self, parse_arguments::InputArguments};
+use genetic_sat::{
+    self, INTERRUPTED_EXIT_CODE,
+    cancellation::Cancellation,
+    parse_arguments::{Command, InputArguments, SolveArguments},
+};
 
-fn main() {
+fn main() -> ExitCode {
     let input_arguments = InputArguments::parse();
-    genetic_sat::run(&input_arguments);
+    let command = input_arguments.command.unwrap_or_else(|| {
+        Command::Solve(SolveArguments {
+            search: input_arguments
+                .search
+                .expect("The search arguments are required without a subcommand"),
+            output: input_arguments.output,
+        })
+    });
+    match command {
+        Command::Solve(solve_arguments) => {
+            genetic_sat::solve(&solve_arguments, &cancel_on_interruption())
+        }
+        Command::Stats(stats_arguments) => genetic_sat::stats(&stats_arguments),
+        Command::Verify(verify_arguments) => genetic_sat::verify(&verify_arguments),
+        Command::Generate(generate_arguments) => genetic_sat::generate(&generate_arguments),
+        Command::Convert(convert_arguments) => genetic_sat::convert(&convert_arguments),
+        Command::Bench(bench_arguments) => {
+            genetic_sat::bench(&bench_arguments, &cancel_on_interruption())
+        }
+    }
+}
+
+/// A cancellation handle cancelled by the first SIGINT or SIGTERM, so that the search can
+/// print its best solution, a second one exiting at once
+fn cancel_on_interruption() -> Cancellation {
+    let cancellation = Cancellation::new();
+    let handler_cancellation = cancellation.clone();
+    if let Err(error) = ctrlc::set_handler(move || {
+        if handler_cancellation.is_cancelled() {
+            process::exit(i32::from(INTERRUPTED_EXIT_CODE));
+        }
+        eprintln!("Interrupted, stopping after the current generation");
+        handler_cancellation.cancel();
+    }) {
+        eprintln!("Warning: could not handle interruptions: {error}");
+    }
+    cancellation
 }
@@ -0,0 +1,197 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, prelude::*};
+use std::path::{Path, PathBuf};
+
+use crate::parse_dimacs;
+use crate::sat::Literal;
+
+/// The values a solver assigned to the variables of a formula, some of them possibly unassigned
+#[derive(Debug, PartialEq)]
+pub struct Model {
+    values: Vec<Option<bool>>,
+}
+
+/// Errors that prevent a model from being read
+#[derive(Debug)]
+pub struct ModelError {
+    pub path: PathBuf,
+    pub kind: ModelErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ModelErrorKind {
+    Io(io::Error),
+    InvalidUtf8 { line: usize },
+    NonLiteralToken { line: usize, token: String },
+    ContradictoryLiterals { line: usize, variable: usize },
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        match &self.kind {
+            ModelErrorKind::Io(error) => write!(f, "{path}: could not read model: {error}"),
+            ModelErrorKind::InvalidUtf8 { line } => {
+                write!(f, "{path}:{line}: line is not valid UTF-8")
+            }
+            ModelErrorKind::NonLiteralToken { line, token } => {
+                write!(f, "{path}:{line}: `{token}` is not a literal")
+            }
+            ModelErrorKind::ContradictoryLiterals { line, variable } => write!(
+                f,
+                "{path}:{line}: variable {variable} is assigned both true and false"
+            ),
+        }
+    }
+}
+
+impl Error for ModelError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ModelErrorKind::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Model {
+    /// Reads the model at `file_path`, or on the standard input when the path is `-`,
+    /// decompressing it like formulas
+    pub fn from_file(file_path: &Path) -> Result<Model, ModelError> {
+        let with_path = |kind| ModelError {
+            path: file_path.to_path_buf(),
+            kind,
+        };
+        let reader =
+            parse_dimacs::open(file_path).map_err(|error| with_path(ModelErrorKind::Io(error)))?;
+        Model::from_reader(reader).map_err(with_path)
+    }
+
+    /// Reads a model in the dimacs model format, the literals set to true on `v` lines
+    /// ending with a 0. Comment and status lines are skipped, as is any other line
+    /// starting with a letter, so that lines of bare literals are read too
+    pub fn from_reader(mut reader: impl BufRead) -> Result<Model, ModelErrorKind> {
+        let mut values = Vec::new();
+        let mut line_bytes = Vec::new();
+        let mut line_number = 0;
+
+        'lines: loop {
+            line_bytes.clear();
+            if reader
+                .read_until(b'\n', &mut line_bytes)
+                .map_err(ModelErrorKind::Io)?
+                == 0
+            {
+                break;
+            }
+            line_number += 1;
+            let line = str::from_utf8(&line_bytes)
+                .map_err(|_| ModelErrorKind::InvalidUtf8 { line: line_number })?
+                .trim();
+            let literals = match line.strip_prefix('v') {
+                Some(literals) => literals,
+                None if line.starts_with(|character: char| character.is_ascii_alphabetic()) => {
+                    continue;
+                }
+                None => line,
+            };
+
+            for token in literals.split_whitespace() {
+                let literal_value: i32 = token
+                    .parse()
+                    .ok()
+                    .filter(|&literal_value| literal_value != i32::MIN)
+                    .ok_or_else(|| ModelErrorKind::NonLiteralToken {
+                        line: line_number,
+                        token: token.to_string(),
+                    })?;
+                if literal_value == 0 {
+                    break 'lines;
+                }
+
+                let variable = usize::try_from(literal_value.unsigned_abs() - 1)
+                    .expect("A 31 bit variable index always fits in a usize");
+                if values.len() <= variable {
+                    values.resize(variable + 1, None);
+                }
+                let value = literal_value > 0;
+                match values[variable] {
+                    Some(assigned_value) if assigned_value != value => {
+                        return Err(ModelErrorKind::ContradictoryLiterals {
+                            line: line_number,
+                            variable: variable + 1,
+                        });
+                    }
+                    _ => values[variable] = Some(value),
+                }
+            }
+        }
+
+        Ok(Model { values })
+    }
+
+    /// The value of the 0-based `variable`, `None` when the model does not assign it
+    #[must_use]
+    pub fn value(&self, variable: usize) -> Option<bool> {
+        self.values.get(variable).copied().flatten()
+    }
+
+    /// Whether one of the literals of the clause is assigned its satisfying value
+    #[must_use]
+    pub fn satisfies_clause(&self, clause: &[Literal]) -> bool {
+        clause
+            .iter()
+            .any(|literal| self.value(literal.variable()) == Some(literal.value()))
+    }
+
+    /// The 0-based variables of a formula of `number_of_variables` the model leaves unassigned
+    #[must_use]
+    pub fn unassigned_variables(&self, number_of_variables: usize) -> Vec<usize> {
+        (0..number_of_variables)
+            .filter(|&variable| self.value(variable).is_none())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_reader_should_read_value_lines_and_skip_comments_and_status() {
+        let model =
+            Model::from_reader("c a comment\ns SATISFIABLE\nv 1 -2\nv 4 0\nv 3 0\n".as_bytes())
+                .expect("The model is well formed");
+
+        assert_eq!(model.value(0), Some(true));
+        assert_eq!(model.value(1), Some(false));
+        assert_eq!(model.value(2), None);
+        assert_eq!(model.value(3), Some(true));
+        assert_eq!(model.unassigned_variables(5), vec![2, 4]);
+    }
+
+    #[test]
+    fn from_reader_should_read_bare_literals() {
+        let model =
+            Model::from_reader("SAT\n-1 2 3 0\n".as_bytes()).expect("The model is well formed");
+
+        assert!(model.satisfies_clause(&[Literal::new(0, false)]));
+        assert!(!model.satisfies_clause(&[Literal::new(0, true), Literal::new(1, false)]));
+        assert!(model.unassigned_variables(3).is_empty());
+    }
+
+    #[test]
+    fn from_reader_should_reject_contradictory_literals() {
+        let error = Model::from_reader("v 1 2 -1 0\n".as_bytes())
+            .expect_err("A variable cannot be both true and false");
+
+        assert!(matches!(
+            error,
+            ModelErrorKind::ContradictoryLiterals {
+                line: 1,
+                variable: 1
+            }
+        ));
+    }
+}
@@ -1,28 +1,459 @@
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 /// SAT solver based on a genetic algorithm
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 pub struct InputArguments {
-    /// The number of SAT solutions considered each iteration
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Solving a formula is the default, without a subcommand
+    #[command(flatten)]
+    pub search: Option<SearchArguments>,
+
+    #[command(flatten)]
+    pub output: OutputArguments,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Solve a formula with the genetic algorithm, the default without a subcommand
+    Solve(SolveArguments),
+    /// Print the structure of a formula: its size, clause lengths, literals and variable occurrences
+    Stats(StatsArguments),
+    /// Check a model against a formula, listing the clauses it leaves unsatisfied
+    Verify(VerifyArguments),
+    /// Write a uniform random k-SAT formula in the dimacs format
+    Generate(GenerateArguments),
+    /// Rewrite a formula in the dimacs format, compressed according to the output extension
+    Convert(ConvertArguments),
+    /// Solve a formula once per seed and summarize the runs
+    Bench(BenchArguments),
+}
+
+/// The formula a subcommand works on. The search arguments repeat these arguments rather than
+/// flattening them, as clap cannot tell whether an optional flattened group nesting another
+/// one is present
+#[derive(Args)]
+pub struct FormulaArguments {
+    /// Path to the CNF formula in the dimacs format, possibly compressed with gzip, xz or bzip2.
+    /// Use - to read the formula from the standard input
+    #[arg(short, long)]
+    pub formula_path: PathBuf,
+
+    /// Accept a formula whose last clause is not terminated by a 0
+    #[arg(long)]
+    pub lenient: bool,
+}
+
+#[derive(Args)]
+pub struct StatsArguments {
+    #[command(flatten)]
+    pub formula: FormulaArguments,
+
+    /// How the statistics are printed
+    #[arg(long, value_enum, default_value_t = StatsFormat::Table)]
+    pub format: StatsFormat,
+}
+
+#[derive(Args)]
+pub struct VerifyArguments {
+    #[command(flatten)]
+    pub formula: FormulaArguments,
+
+    /// Path to the model, the literals set to true on `v` lines ending with a 0
+    #[arg(short, long)]
+    pub model_path: PathBuf,
+}
+
+#[derive(Args)]
+pub struct GenerateArguments {
+    /// The number of variables of the formula
+    #[arg(short = 'n', long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub variables: u32,
+
+    /// The number of clauses of the formula
+    #[arg(short = 'm', long)]
+    pub clauses: u32,
+
+    /// The number of distinct variables of each clause, at most the number of variables
+    #[arg(short = 'k', long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub clause_length: u32,
+
+    /// Only keep clauses satisfied by a hidden random solution, so that the formula is satisfiable
+    #[arg(long)]
+    pub planted: bool,
+
+    /// Seed of the random number generator, the formula can be generated again by reusing it
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Where to write the formula, compressed with gzip, xz or bzip2 when the path ends with
+    /// .gz, .xz or .bz2. The formula is written on the standard output by default
+    #[arg(short, long, default_value = "-")]
+    pub output: PathBuf,
+}
+
+#[derive(Args)]
+pub struct ConvertArguments {
+    #[command(flatten)]
+    pub formula: FormulaArguments,
+
+    /// Where to write the formula, compressed with gzip, xz or bzip2 when the path ends with
+    /// .gz, .xz or .bz2. Use - to write it on the standard output
     #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+#[derive(Args)]
+pub struct BenchArguments {
+    /// The number of runs, the seed of each run being one more than the seed of the previous one
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    pub runs: u32,
+
+    #[command(flatten)]
+    pub search: SearchArguments,
+}
+
+#[derive(Args)]
+pub struct SolveArguments {
+    #[command(flatten)]
+    pub search: SearchArguments,
+
+    #[command(flatten)]
+    pub output: OutputArguments,
+}
+
+/// The formula to solve and how the genetic algorithm searches for a solution
+#[derive(Args)]
+pub struct SearchArguments {
+    /// The number of SAT solutions considered each iteration
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..))]
     pub population_size: u32,
 
     /// The probability of a solution to randomly change during an iteration
-    #[arg(short = 'u', long)]
+    #[arg(short = 'u', long, value_parser = parse_probability::<f32>)]
     pub mutation_probability: f32,
 
-    /// The maximum number of breeding individuals in each generation
-    #[arg(short = 'b', long, default_value_t = 10)]
+    /// The maximum number of breeding individuals in each generation, at least 2 to form a couple
+    #[arg(short = 'b', long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(2..))]
     pub maximum_of_breeding_individuals_in_a_generation: u32,
 
     /// The number of iteration of the algorithm before it stops even if no perfect solution is found
     #[arg(short, long)]
     pub maximum_number_of_generations: u32,
 
-    /// Path to the CNF formula in the dimacs format
+    /// Path to the CNF formula in the dimacs format, possibly compressed with gzip, xz or bzip2.
+    /// Use - to read the formula from the standard input
     #[arg(short, long)]
     pub formula_path: PathBuf,
+
+    /// Seed of the random number generator, a run can be reproduced by reusing the seed it printed
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// How the breeding individuals of each generation are selected
+    #[arg(long, value_enum, default_value_t = SelectionStrategy::Roulette)]
+    pub selection: SelectionStrategy,
+
+    /// The number of individuals competing in each tournament of the tournament selection,
+    /// and of the tournament loser replacement
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+    pub tournament_size: u32,
+
+    /// How much more likely the fittest individual is to be selected than an average one
+    /// with the rank selection, between 1 and 2
+    #[arg(long, default_value_t = 1.5, value_parser = parse_selection_pressure)]
+    pub selection_pressure: f64,
+
+    /// The proportion of the fittest individuals kept by the truncation selection, between 0 and 1
+    #[arg(long, default_value_t = 0.5, value_parser = parse_truncation_proportion)]
+    pub truncation_proportion: f64,
+
+    /// How two breeding individuals are combined into an embryo
+    #[arg(long, value_enum, default_value_t = CrossoverOperator::Uniform)]
+    pub crossover: CrossoverOperator,
+
+    /// The number of cut points of the k-point crossover
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub crossover_points: u32,
+
+    /// The probability of the biased uniform crossover to take a literal from the first parent
+    #[arg(long, default_value_t = 0.7, value_parser = parse_probability::<f64>)]
+    pub crossover_bias: f64,
+
+    /// How an embryo is altered when it mutates
+    #[arg(long, value_enum, default_value_t = MutationOperator::KFlip)]
+    pub mutation: MutationOperator,
+
+    /// The probability of each variable to flip with the bit-flip mutation,
+    /// one over the number of variables by default
+    #[arg(long, value_parser = parse_probability::<f64>)]
+    pub mutation_rate: Option<f64>,
+
+    /// The number of variables flipped by the k-flip and focused mutations
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub mutation_flips: u32,
+
+    /// The number of fittest individuals copied unchanged into the next generation,
+    /// at most the population size
+    #[arg(long, default_value_t = 0)]
+    pub elitism: u32,
+
+    /// How each generation replaces the previous one
+    #[arg(long, value_enum, default_value_t = ReplacementScheme::Generational)]
+    pub scheme: ReplacementScheme,
+
+    /// Which individual each child replaces with the steady-state scheme
+    #[arg(long, value_enum, default_value_t = SteadyStateReplacement::Worst)]
+    pub replacement: SteadyStateReplacement,
+
+    /// Local search improving each child once it is mutated
+    #[arg(long, value_enum)]
+    pub local_search: Option<LocalSearchAlgorithm>,
+
+    /// The maximum number of flips of each local search
+    #[arg(long, default_value_t = 100)]
+    pub local_search_flips: u64,
+
+    /// The probability of the walksat local search to flip a random variable of the clause
+    /// rather than the one breaking the fewest clauses
+    #[arg(long, default_value_t = 0.5, value_parser = parse_probability::<f64>)]
+    pub noise: f64,
+
+    /// The number of fittest children the local search improves each generation,
+    /// all of them by default
+    #[arg(long)]
+    pub local_search_individuals: Option<u32>,
+
+    /// How individuals are scored when they compete to breed and to survive
+    #[arg(long, value_enum, default_value_t = FitnessMode::Ratio)]
+    pub fitness: FitnessMode,
+
+    /// The number of generations between two updates of the clause weights with saw fitness
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub saw_interval: u32,
+
+    /// How much the weight of a clause grows when the best individual does not satisfy it
+    /// with saw fitness
+    #[arg(long, default_value_t = 1., value_parser = parse_weight_increment)]
+    pub saw_increment: f64,
+
+    /// The number of threads evolving the islands and evaluating and improving the individuals,
+    /// 0 to use every core. A seeded run gives the same result whatever the number of threads
+    #[arg(long, default_value_t = 0)]
+    pub threads: usize,
+
+    /// The number of populations evolving side by side and exchanging individuals,
+    /// 1 for a single population. Islands only evolve in parallel with at least as many threads
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub islands: u32,
+
+    /// The number of generations between two migrations between islands
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    pub migration_interval: u32,
+
+    /// The number of fittest individuals each island sends at each migration
+    #[arg(long, default_value_t = 1)]
+    pub migrants: u32,
+
+    /// Which islands the migrants of each island go to
+    #[arg(long, value_enum, default_value_t = MigrationTopology::Ring)]
+    pub topology: MigrationTopology,
+
+    /// Comma separated mutation probabilities of the islands, cycled over when there are
+    /// more islands than probabilities. All islands use the mutation probability by default
+    #[arg(long, value_delimiter = ',', value_parser = parse_probability::<f32>)]
+    pub island_mutation_probabilities: Vec<f32>,
+
+    /// Stop the search after this many seconds
+    #[arg(long, value_parser = parse_time_limit)]
+    pub time_limit: Option<Duration>,
+
+    /// Stop the search once this many individuals have been evaluated,
+    /// by each island with the island model
+    #[arg(long)]
+    pub max_evaluations: Option<u64>,
+
+    /// Stop the search after this many generations without improving the best fitness
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub stagnation: Option<u32>,
+
+    /// Accept a formula whose last clause is not terminated by a 0
+    #[arg(long)]
+    pub lenient: bool,
+}
+
+/// What a single solve prints and writes besides its progress
+#[derive(Args)]
+pub struct OutputArguments {
+    /// Write the weight of each clause at the end of the run to this file
+    #[arg(long)]
+    pub dump_weights: Option<PathBuf>,
+
+    /// How the progress of the search and its result are printed
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    pub output_format: OutputFormat,
+
+    /// Write the status and the best solution to this file, in the dimacs model format
+    #[arg(long)]
+    pub output_model: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SelectionStrategy {
+    /// Fitness proportional sampling without replacement
+    Roulette,
+    /// The fittest of randomly drawn individuals
+    Tournament,
+    /// Sampling weighted by the rank of the individuals
+    Rank,
+    /// Stochastic universal sampling
+    Sus,
+    /// Uniform sampling among the fittest individuals
+    Truncation,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CrossoverOperator {
+    /// Each literal comes from either parent with the same probability
+    Uniform,
+    /// Each literal comes from the first parent with the crossover bias probability
+    BiasedUniform,
+    /// The parents are cut at one random position
+    OnePoint,
+    /// The parents are cut at two random positions
+    TwoPoint,
+    /// The parents are cut at as many random positions as crossover points
+    KPoint,
+    /// The variables of clauses satisfied by a single parent come from that parent
+    ClauseAware,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum MutationOperator {
+    /// Each variable flips independently with the mutation rate
+    BitFlip,
+    /// As many distinct random variables as mutation flips are flipped
+    KFlip,
+    /// Variables of randomly chosen unsatisfied clauses are flipped, as many as mutation flips
+    Focused,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ReplacementScheme {
+    /// The children of a generation replace the whole population, but for its elite
+    Generational,
+    /// Children are bred one at a time, each replacing a single individual,
+    /// and children already in the population are discarded
+    SteadyState,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SteadyStateReplacement {
+    /// The least fit individual of the population
+    Worst,
+    /// The least fit of randomly drawn individuals
+    TournamentLoser,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum LocalSearchAlgorithm {
+    /// Flips a variable of a random unsatisfied clause, the one breaking the fewest clauses
+    /// or a random one with the noise probability
+    Walksat,
+    /// Flips every variable satisfying more clauses than it breaks until none is left
+    Greedy,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum FitnessMode {
+    /// The ratio of satisfied clauses
+    Ratio,
+    /// Stepwise adaptation of weights, the clauses the best individual does not satisfy
+    /// weigh more and more in the fitness
+    Saw,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum MigrationTopology {
+    /// Each island sends its migrants to the next one, the last island to the first
+    Ring,
+    /// Each island sends its migrants to every other island
+    FullyConnected,
+    /// Each island sends its migrants to another island drawn at random at each migration
+    Random,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Sentences describing the search and its best solution
+    Human,
+    /// The SAT competition format: comment lines, a status line and the model on value lines,
+    /// exiting with 10 when the formula is satisfied, 20 when it has an empty clause and 0 otherwise
+    Competition,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StatsFormat {
+    /// A statistic per line, its name followed by its value
+    Table,
+    /// A JSON object with a field per statistic
+    Json,
+}
+
+fn parse_time_limit(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value.parse().map_err(|error| format!("{error}"))?;
+    Duration::try_from_secs_f64(seconds).map_err(|error| format!("{error}"))
+}
+
+fn parse_weight_increment(value: &str) -> Result<f64, String> {
+    let increment: f64 = value.parse().map_err(|error| format!("{error}"))?;
+    if increment > 0. && increment.is_finite() {
+        Ok(increment)
+    } else {
+        Err(String::from(
+            "the weight increment should be a positive number",
+        ))
+    }
+}
+
+fn parse_probability<T>(value: &str) -> Result<T, String>
+where
+    T: FromStr + PartialOrd + From<u8>,
+    T::Err: fmt::Display,
+{
+    let probability: T = value.parse().map_err(|error| format!("{error}"))?;
+    if (T::from(0)..=T::from(1)).contains(&probability) {
+        Ok(probability)
+    } else {
+        Err(String::from("a probability should be between 0 and 1"))
+    }
+}
+
+fn parse_selection_pressure(value: &str) -> Result<f64, String> {
+    let selection_pressure: f64 = value.parse().map_err(|error| format!("{error}"))?;
+    if (1. ..=2.).contains(&selection_pressure) {
+        Ok(selection_pressure)
+    } else {
+        Err(String::from(
+            "the selection pressure should be between 1 and 2",
+        ))
+    }
+}
+
+fn parse_truncation_proportion(value: &str) -> Result<f64, String> {
+    let proportion: f64 = value.parse().map_err(|error| format!("{error}"))?;
+    if proportion > 0. && proportion <= 1. {
+        Ok(proportion)
+    } else {
+        Err(String::from(
+            "the truncation proportion should be greater than 0 and at most 1",
+        ))
+    }
 }
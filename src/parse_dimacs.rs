@@ -1,83 +1,537 @@
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
+use std::io::{self, BufReader, prelude::*};
+use std::num::{IntErrorKind, ParseIntError};
+use std::path::{Path, PathBuf};
 
-use std::io::{BufReader, prelude::*};
-use std::path::Path;
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use liblzma::read::XzDecoder;
 
-use crate::sat::{Clause, Formula};
+use crate::sat::{Formula, Literal};
 
-pub fn from_file(file_path: &Path) -> Formula {
-    let file = File::open(file_path).expect("Could not read formula file");
-    let reader = BufReader::new(file);
-    let mut clauses = Vec::new();
-    let mut all_literals: HashSet<usize> = HashSet::new();
+/// Problems found while parsing a formula that do not prevent building it,
+/// but mean that the file does not match its own `p cnf` header
+#[derive(Debug, PartialEq)]
+pub enum Diagnostic {
+    MissingHeader,
+    TooManyClauses {
+        declared: usize,
+        found: usize,
+    },
+    TooFewClauses {
+        declared: usize,
+        found: usize,
+    },
+    /// Reported once per variable, on the first line it appears on
+    VariableAboveDeclaredMaximum {
+        line: usize,
+        variable: usize,
+        declared: usize,
+    },
+    /// The header declares variables beyond the highest one the clauses use,
+    /// which are left out of the formula
+    UnusedDeclaredVariables {
+        declared: usize,
+        highest: usize,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::MissingHeader => write!(
+                f,
+                "the formula has no `p cnf` header, the number of variables is inferred from the clauses"
+            ),
+            Diagnostic::TooManyClauses { declared, found } => write!(
+                f,
+                "the header declares {declared} clauses but {found} were found"
+            ),
+            Diagnostic::TooFewClauses { declared, found } => write!(
+                f,
+                "the header declares {declared} clauses but only {found} were found"
+            ),
+            Diagnostic::VariableAboveDeclaredMaximum {
+                line,
+                variable,
+                declared,
+            } => write!(
+                f,
+                "line {line}: variable {variable} is above the {declared} variables declared in the header"
+            ),
+            Diagnostic::UnusedDeclaredVariables { declared, highest } => write!(
+                f,
+                "the header declares {declared} variables but the clauses use none above {highest}, the formula only has {highest} variables"
+            ),
+        }
+    }
+}
+
+/// Errors that prevent a formula from being built
+#[derive(Debug)]
+pub struct ParseError {
+    pub path: PathBuf,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    Io(io::Error),
+    InvalidUtf8 {
+        line: usize,
+    },
+    NonIntegerToken {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    LiteralOverflow {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    BadHeader {
+        line: usize,
+        header: String,
+    },
+    UnterminatedClause {
+        line: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        match &self.kind {
+            ParseErrorKind::Io(error) => write!(f, "{path}: could not read formula: {error}"),
+            ParseErrorKind::InvalidUtf8 { line } => {
+                write!(f, "{path}:{line}: line is not valid UTF-8")
+            }
+            ParseErrorKind::NonIntegerToken {
+                line,
+                column,
+                token,
+            } => write!(f, "{path}:{line}:{column}: `{token}` is not an integer"),
+            ParseErrorKind::LiteralOverflow {
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "{path}:{line}:{column}: literal `{token}` is too large to be a variable index"
+            ),
+            ParseErrorKind::BadHeader { line, header } => write!(
+                f,
+                "{path}:{line}: `{header}` is not of the form `p cnf <variables> <clauses>`"
+            ),
+            ParseErrorKind::UnterminatedClause { line } => write!(
+                f,
+                "{path}:{line}: the last clause, starting on this line, is not terminated by a 0"
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// How to treat a last clause that is not terminated by a 0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strictness {
+    Strict,
+    Lenient,
+}
+
+/// Whether to simplify the clauses while parsing them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Simplification {
+    /// Drop tautological clauses and repeated literals, as they do not change
+    /// which solutions satisfy the formula
+    Simplify,
+    /// Keep every clause as written in the file, to describe the file itself
+    Keep,
+}
+
+/// The number of variables and clauses declared by the `p cnf` line
+#[derive(Debug, PartialEq)]
+struct Header {
+    number_of_variables: usize,
+    number_of_clauses: usize,
+}
+
+/// Clauses and literals that were simplified away while parsing, none when they are kept
+#[derive(Debug, Default, PartialEq)]
+pub struct ParseStatistics {
+    /// Clauses containing both a literal and its negation, dropped as they are always satisfied
+    pub tautological_clauses: usize,
+    /// Literals repeated within a clause, only the first occurrence is kept
+    pub duplicate_literals: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParsedFormula {
+    pub formula: Formula,
+    pub diagnostics: Vec<Diagnostic>,
+    pub statistics: ParseStatistics,
+    /// The 0-based position in the file of each clause of the formula, which differs from
+    /// its index in the formula once tautological clauses are dropped
+    pub clause_indices: Vec<usize>,
+}
+
+/// Parses the formula at `file_path`, or on the standard input when the path is `-`.
+/// Formulae compressed with gzip, xz or bzip2 are detected from their first bytes
+/// and decompressed on the fly
+pub fn from_file(
+    file_path: &Path,
+    strictness: Strictness,
+    simplification: Simplification,
+) -> Result<ParsedFormula, ParseError> {
+    let with_path = |kind| ParseError {
+        path: file_path.to_path_buf(),
+        kind,
+    };
+    let reader = open(file_path).map_err(|error| with_path(ParseErrorKind::Io(error)))?;
+    from_reader(reader, strictness, simplification).map_err(with_path)
+}
 
-    for line in reader.lines() {
-        let literal_values_as_string =
-            line.expect("Formula file contains non valid UTF8 character");
-        if literal_values_as_string.starts_with('c') || literal_values_as_string.starts_with('p') {
+/// The compression formats a formula can be read from, told apart by their magic bytes
+#[derive(Debug, PartialEq)]
+enum Compression {
+    None,
+    Gzip,
+    Xz,
+    Bzip2,
+}
+
+impl Compression {
+    const LONGEST_MAGIC_BYTES: usize = 6;
+
+    fn detect(first_bytes: &[u8]) -> Self {
+        if first_bytes.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if first_bytes.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else if first_bytes.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Opens `file_path` for reading, or the standard input when the path is `-`,
+/// decompressing it on the fly when it starts with the magic bytes of a known format
+pub fn open(file_path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let mut reader: Box<dyn Read> = if file_path == Path::new("-") {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(file_path)?)
+    };
+
+    let mut first_bytes = Vec::with_capacity(Compression::LONGEST_MAGIC_BYTES);
+    reader
+        .by_ref()
+        .take(Compression::LONGEST_MAGIC_BYTES as u64)
+        .read_to_end(&mut first_bytes)?;
+    let compression = Compression::detect(&first_bytes);
+    let reader = io::Cursor::new(first_bytes).chain(reader);
+
+    Ok(match compression {
+        Compression::None => Box::new(BufReader::new(reader)),
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Compression::Xz => Box::new(BufReader::new(XzDecoder::new_multi_decoder(reader))),
+        Compression::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(reader))),
+    })
+}
+
+pub fn from_reader(
+    mut reader: impl BufRead,
+    strictness: Strictness,
+    simplification: Simplification,
+) -> Result<ParsedFormula, ParseErrorKind> {
+    let mut formula = Formula::new(0);
+    let mut diagnostics = Vec::new();
+    let mut header: Option<Header> = None;
+    let mut highest_variable = 0;
+    let mut line_bytes = Vec::new();
+    let mut line_number = 0;
+    let mut statistics = ParseStatistics::default();
+    let mut clause_literals = Vec::new();
+    let mut parsed_clauses = 0;
+    let mut clause_indices = Vec::new();
+    let mut clause_first_line: Option<usize> = None;
+    let mut reported_variables = HashSet::new();
+
+    loop {
+        line_bytes.clear();
+        if reader
+            .read_until(b'\n', &mut line_bytes)
+            .map_err(ParseErrorKind::Io)?
+            == 0
+        {
+            break;
+        }
+        line_number += 1;
+        let literal_values_as_string = str::from_utf8(&line_bytes)
+            .map_err(|_| ParseErrorKind::InvalidUtf8 { line: line_number })?
+            .trim_end();
+        if literal_values_as_string.starts_with('c') {
+            continue;
+        }
+        if literal_values_as_string.starts_with('p') {
+            header = Some(parse_header(literal_values_as_string, line_number)?);
             continue;
         }
         if literal_values_as_string.contains('%') {
             break;
         }
 
-        let clause = parse_clause(&literal_values_as_string);
-        let literals_set: HashSet<usize> = clause.literals.clone().into_keys().collect();
-        all_literals.extend(&literals_set);
-        clauses.push(clause);
+        for (column, literal_value_as_string) in tokens_with_columns(literal_values_as_string) {
+            let literal_value = parse_literal(literal_value_as_string, line_number, column)?;
+            if literal_value == 0 {
+                if push_clause(
+                    &mut formula,
+                    &mut clause_literals,
+                    &mut statistics,
+                    simplification,
+                ) {
+                    clause_indices.push(parsed_clauses);
+                }
+                parsed_clauses += 1;
+                clause_first_line = None;
+                continue;
+            }
+
+            clause_first_line.get_or_insert(line_number);
+            let variable = usize::try_from(literal_value.unsigned_abs())
+                .expect("A 31 bit variable index always fits in a usize");
+            if let Some(header) = &header
+                && variable > header.number_of_variables
+                && reported_variables.insert(variable)
+            {
+                diagnostics.push(Diagnostic::VariableAboveDeclaredMaximum {
+                    line: line_number,
+                    variable,
+                    declared: header.number_of_variables,
+                });
+            }
+            highest_variable = highest_variable.max(variable);
+            clause_literals.push(Literal::new(variable - 1, literal_value > 0));
+        }
     }
 
-    let number_of_literals = all_literals.len();
-    Formula {
-        clauses,
-        number_of_literals,
+    if let Some(line) = clause_first_line {
+        match strictness {
+            Strictness::Strict => return Err(ParseErrorKind::UnterminatedClause { line }),
+            Strictness::Lenient => {
+                if push_clause(
+                    &mut formula,
+                    &mut clause_literals,
+                    &mut statistics,
+                    simplification,
+                ) {
+                    clause_indices.push(parsed_clauses);
+                }
+                parsed_clauses += 1;
+            }
+        }
     }
+
+    formula.number_of_variables =
+        number_of_variables(header, highest_variable, parsed_clauses, &mut diagnostics);
+
+    Ok(ParsedFormula {
+        formula,
+        diagnostics,
+        statistics,
+        clause_indices,
+    })
 }
 
-fn parse_clause(clause_as_string: &str) -> Clause {
-    let mut literals = HashMap::new();
+/// The number of variables of the formula, the highest variable its clauses use, so that
+/// a header declaring too many variables cannot make every solution needlessly large.
+/// Warns when the header does not match the clauses or when there is no header
+fn number_of_variables(
+    header: Option<Header>,
+    highest_variable: usize,
+    parsed_clauses: usize,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> usize {
+    if let Some(header) = header {
+        match parsed_clauses.cmp(&header.number_of_clauses) {
+            Ordering::Greater => diagnostics.push(Diagnostic::TooManyClauses {
+                declared: header.number_of_clauses,
+                found: parsed_clauses,
+            }),
+            Ordering::Less => diagnostics.push(Diagnostic::TooFewClauses {
+                declared: header.number_of_clauses,
+                found: parsed_clauses,
+            }),
+            Ordering::Equal => (),
+        }
+        if header.number_of_variables > highest_variable {
+            diagnostics.push(Diagnostic::UnusedDeclaredVariables {
+                declared: header.number_of_variables,
+                highest: highest_variable,
+            });
+        }
+    } else {
+        diagnostics.push(Diagnostic::MissingHeader);
+    }
+    highest_variable
+}
 
-    for literal_value_as_string in clause_as_string.split_whitespace() {
-        let literal_value: i32 = literal_value_as_string
-            .parse()
-            .expect("Formula file contains a literal that is not an interger");
-        match literal_value.cmp(&0) {
-            Ordering::Less => {
-                let literal_key = usize::try_from(literal_value.abs() - 1)
-                    .expect("The absolute value of the literal key is higher greater than 0, substracting 1 from it would still make it a valid usize");
-                literals.insert(literal_key, false);
+/// Pushes the clause to the formula, simplified unless the simplification says to keep it,
+/// returning whether the clause was kept
+fn push_clause(
+    formula: &mut Formula,
+    literals: &mut Vec<Literal>,
+    statistics: &mut ParseStatistics,
+    simplification: Simplification,
+) -> bool {
+    match simplification {
+        Simplification::Simplify => push_simplified_clause(formula, literals, statistics),
+        Simplification::Keep => {
+            formula.push_clause(literals);
+            literals.clear();
+            true
+        }
+    }
+}
+
+/// Removes repeated literals from a clause and pushes it to the formula,
+/// unless it contains a literal and its negation, returning whether the clause was kept.
+/// The clause literals are cleared so they can be reused for the next clause
+fn push_simplified_clause(
+    formula: &mut Formula,
+    literals: &mut Vec<Literal>,
+    statistics: &mut ParseStatistics,
+) -> bool {
+    let mut number_of_kept_literals = 0;
+    for literal_index in 0..literals.len() {
+        let literal = literals[literal_index];
+        match literals[..number_of_kept_literals]
+            .iter()
+            .find(|kept_literal| kept_literal.variable() == literal.variable())
+        {
+            Some(kept_literal) if *kept_literal == literal => {
+                statistics.duplicate_literals += 1;
             }
-            Ordering::Greater => {
-                let literal_key = usize::try_from(literal_value - 1).expect("The value of the literal key is strictly greater than 0, subtracting 1 from it makes it a valid usize");
-                literals.insert(literal_key, true);
+            Some(_) => {
+                statistics.tautological_clauses += 1;
+                literals.clear();
+                return false;
+            }
+            None => {
+                literals[number_of_kept_literals] = literal;
+                number_of_kept_literals += 1;
             }
-            Ordering::Equal => (),
         }
     }
 
-    Clause { literals }
+    literals.truncate(number_of_kept_literals);
+    formula.push_clause(literals);
+    literals.clear();
+    true
+}
+
+fn parse_header(header_as_string: &str, line: usize) -> Result<Header, ParseErrorKind> {
+    let bad_header = || ParseErrorKind::BadHeader {
+        line,
+        header: header_as_string.to_string(),
+    };
+    let mut fields = header_as_string.split_whitespace();
+    if fields.next() != Some("p") || fields.next() != Some("cnf") {
+        return Err(bad_header());
+    }
+    let number_of_variables = fields
+        .next()
+        .and_then(|field| field.parse().ok())
+        .ok_or_else(bad_header)?;
+    let number_of_clauses = fields
+        .next()
+        .and_then(|field| field.parse().ok())
+        .ok_or_else(bad_header)?;
+    if fields.next().is_some() {
+        return Err(bad_header());
+    }
+
+    Ok(Header {
+        number_of_variables,
+        number_of_clauses,
+    })
+}
+
+/// Splits a line on whitespace, pairing each token with the 1-based column it starts at
+fn tokens_with_columns(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace()
+        .map(move |token| (token.as_ptr().addr() - line.as_ptr().addr() + 1, token))
+}
+
+fn parse_literal(token: &str, line: usize, column: usize) -> Result<i32, ParseErrorKind> {
+    token
+        .parse()
+        .map_err(|error: ParseIntError| match error.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                ParseErrorKind::LiteralOverflow {
+                    line,
+                    column,
+                    token: token.to_string(),
+                }
+            }
+            _ => ParseErrorKind::NonIntegerToken {
+                line,
+                column,
+                token: token.to_string(),
+            },
+        })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use tempfile::tempdir;
+    use tempfile::{TempDir, tempdir};
+
+    fn literals(variables_and_values: &[(usize, bool)]) -> Vec<Literal> {
+        variables_and_values
+            .iter()
+            .map(|&(variable, value)| Literal::new(variable, value))
+            .collect()
+    }
+
+    fn clauses(formula: &Formula) -> Vec<Vec<Literal>> {
+        formula.clauses().map(<[Literal]>::to_vec).collect()
+    }
+
+    fn parse_single_clause(clause_as_string_slice: &str) -> Vec<Literal> {
+        let parsed_formula = from_reader(
+            clause_as_string_slice.as_bytes(),
+            Strictness::Lenient,
+            Simplification::Simplify,
+        )
+        .expect("The test clause is well formed");
+        assert_eq!(parsed_formula.formula.number_of_clauses(), 1);
+        parsed_formula.formula.clause(0).to_vec()
+    }
 
     #[test]
     fn test_parse_clause_offsets_literals_by_minus_1() {
         let clause_as_string_slice = "1 -2 -3";
 
-        let mut expected_literals = HashMap::new();
-        expected_literals.insert(0, true);
-        expected_literals.insert(1, false);
-        expected_literals.insert(2, false);
-        let expected_clause = Clause {
-            literals: expected_literals,
-        };
+        let expected_literals = vec![(0, true), (1, false), (2, false)];
+        let expected_clause = literals(&expected_literals);
 
-        let clause = parse_clause(clause_as_string_slice);
+        let clause = parse_single_clause(clause_as_string_slice);
 
         assert_eq!(clause, expected_clause);
     }
@@ -86,13 +540,10 @@ mod test {
     fn test_parse_clause_ignores_0() {
         let clause_as_string_slice = "1 0";
 
-        let mut expected_literals = HashMap::new();
-        expected_literals.insert(0, true);
-        let expected_clause = Clause {
-            literals: expected_literals,
-        };
+        let expected_literals = vec![(0, true)];
+        let expected_clause = literals(&expected_literals);
 
-        let clause = parse_clause(clause_as_string_slice);
+        let clause = parse_single_clause(clause_as_string_slice);
 
         assert_eq!(clause, expected_clause);
     }
@@ -101,14 +552,10 @@ mod test {
     fn test_parse_clause_ignores_space_at_the_begining_of_the_clause() {
         let clause_as_string_slice = " 21 4";
 
-        let mut expected_literals = HashMap::new();
-        expected_literals.insert(20, true);
-        expected_literals.insert(3, true);
-        let expected_clause = Clause {
-            literals: expected_literals,
-        };
+        let expected_literals = vec![(20, true), (3, true)];
+        let expected_clause = literals(&expected_literals);
 
-        let clause = parse_clause(clause_as_string_slice);
+        let clause = parse_single_clause(clause_as_string_slice);
 
         assert_eq!(clause, expected_clause);
     }
@@ -141,48 +588,35 @@ p cnf 4  5
         )
         .expect("Could not write content in dimacs file");
 
-        let mut expected_first_clause_literals = HashMap::new();
-        expected_first_clause_literals.insert(0, true);
-        expected_first_clause_literals.insert(1, false);
-        expected_first_clause_literals.insert(2, true);
-        expected_first_clause_literals.insert(3, true);
-        let mut expected_second_clause_literals = HashMap::new();
-        expected_second_clause_literals.insert(2, true);
-        let mut expected_third_clause_literals = HashMap::new();
-        expected_third_clause_literals.insert(1, false);
-        let mut expected_fourth_clause_literals = HashMap::new();
-        expected_fourth_clause_literals.insert(0, true);
-        let mut expected_fifth_clause_literals = HashMap::new();
-        expected_fifth_clause_literals.insert(3, true);
-        let expected_first_clause = Clause {
-            literals: expected_first_clause_literals,
-        };
-        let expected_second_clause = Clause {
-            literals: expected_second_clause_literals,
-        };
-        let expected_third_clause = Clause {
-            literals: expected_third_clause_literals,
-        };
-        let expected_fourth_clause = Clause {
-            literals: expected_fourth_clause_literals,
-        };
-        let expected_fifth_clause = Clause {
-            literals: expected_fifth_clause_literals,
-        };
-        let expected_formula = Formula {
-            clauses: vec![
-                expected_first_clause,
-                expected_second_clause,
-                expected_third_clause,
-                expected_fourth_clause,
-                expected_fifth_clause,
-            ],
-            number_of_literals: 4,
-        };
-
-        let formula = from_file(dimacs_file_path.as_path());
-
-        assert_eq!(formula, expected_formula);
+        let expected_first_clause_literals = vec![(0, true), (1, false), (2, true), (3, true)];
+        let expected_second_clause_literals = vec![(2, true)];
+        let expected_third_clause_literals = vec![(1, false)];
+        let expected_fourth_clause_literals = vec![(0, true)];
+        let expected_fifth_clause_literals = vec![(3, true)];
+        let expected_first_clause = literals(&expected_first_clause_literals);
+        let expected_second_clause = literals(&expected_second_clause_literals);
+        let expected_third_clause = literals(&expected_third_clause_literals);
+        let expected_fourth_clause = literals(&expected_fourth_clause_literals);
+        let expected_fifth_clause = literals(&expected_fifth_clause_literals);
+        let mut expected_formula = Formula::new(4);
+        for expected_clause in [
+            expected_first_clause,
+            expected_second_clause,
+            expected_third_clause,
+            expected_fourth_clause,
+            expected_fifth_clause,
+        ] {
+            expected_formula.push_clause(&expected_clause);
+        }
+
+        let parsed_formula = from_file(
+            dimacs_file_path.as_path(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The test formula is well formed");
+
+        assert_eq!(parsed_formula.formula, expected_formula);
     }
 
     #[test]
@@ -215,8 +649,447 @@ p cnf 4  5
 
         let expected_number_of_literals = 4;
 
-        let formula = from_file(&dimacs_file_path);
+        let parsed_formula = from_file(
+            &dimacs_file_path,
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The test formula is well formed");
+
+        assert_eq!(
+            parsed_formula.formula.number_of_variables,
+            expected_number_of_literals
+        );
+    }
+
+    fn write_dimacs_file(tmp_dir: &TempDir, content: &str) -> PathBuf {
+        let dimacs_file_path = tmp_dir.path().join("dimacs.cnf");
+        let mut dimacs_file =
+            File::create(&dimacs_file_path).expect("Temporary test file could not be created");
+        write!(dimacs_file, "{content}").expect("Could not write content in dimacs file");
+        dimacs_file_path
+    }
+
+    #[test]
+    fn parse_dimacs_formula_from_file_should_size_the_formula_with_the_declared_number_of_variables()
+     {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = write_dimacs_file(&tmp_dir, "p cnf 10 3\n1 5 0\n-10 0\n5 -1 0\n");
+
+        let parsed_formula = from_file(
+            &dimacs_file_path,
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The test formula is well formed");
+
+        assert_eq!(parsed_formula.formula.number_of_variables, 10);
+        assert!(parsed_formula.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn parse_dimacs_formula_from_file_should_report_too_many_clauses() {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = write_dimacs_file(&tmp_dir, "p cnf 2 1\n1 0\n-2 0\n");
+
+        let parsed_formula = from_file(
+            &dimacs_file_path,
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The test formula is well formed");
+
+        assert_eq!(
+            parsed_formula.diagnostics,
+            vec![Diagnostic::TooManyClauses {
+                declared: 1,
+                found: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_dimacs_formula_from_file_should_report_too_few_clauses() {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = write_dimacs_file(&tmp_dir, "p cnf 2 3\n1 0\n-2 0\n");
+
+        let parsed_formula = from_file(
+            &dimacs_file_path,
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The test formula is well formed");
+
+        assert_eq!(
+            parsed_formula.diagnostics,
+            vec![Diagnostic::TooFewClauses {
+                declared: 3,
+                found: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_dimacs_formula_from_file_should_report_and_make_room_for_variables_above_the_declared_maximum()
+     {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = write_dimacs_file(&tmp_dir, "p cnf 2 2\n1 0\n-4 2 0\n");
+
+        let parsed_formula = from_file(
+            &dimacs_file_path,
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The test formula is well formed");
+
+        assert_eq!(
+            parsed_formula.diagnostics,
+            vec![Diagnostic::VariableAboveDeclaredMaximum {
+                line: 3,
+                variable: 4,
+                declared: 2
+            }]
+        );
+        assert_eq!(parsed_formula.formula.number_of_variables, 4);
+    }
+
+    #[test]
+    fn parse_should_report_each_variable_above_the_declared_maximum_once() {
+        let parsed_formula = from_reader(
+            "p cnf 2 3\n3 1 0\n-3 2 0\n3 -4 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The formula is well formed");
+
+        assert_eq!(
+            parsed_formula.diagnostics,
+            vec![
+                Diagnostic::VariableAboveDeclaredMaximum {
+                    line: 2,
+                    variable: 3,
+                    declared: 2
+                },
+                Diagnostic::VariableAboveDeclaredMaximum {
+                    line: 4,
+                    variable: 4,
+                    declared: 2
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_should_leave_out_declared_variables_no_clause_uses() {
+        let parsed_formula = from_reader(
+            "p cnf 4000000000 1\n1 -2 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The formula is well formed");
+
+        assert_eq!(parsed_formula.formula.number_of_variables, 2);
+        assert_eq!(
+            parsed_formula.diagnostics,
+            vec![Diagnostic::UnusedDeclaredVariables {
+                declared: 4_000_000_000,
+                highest: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_dimacs_formula_from_file_without_header_should_use_the_highest_variable() {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = write_dimacs_file(&tmp_dir, "1 0\n-7 2 0\n");
+
+        let parsed_formula = from_file(
+            &dimacs_file_path,
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The test formula is well formed");
+
+        assert_eq!(parsed_formula.diagnostics, vec![Diagnostic::MissingHeader]);
+        assert_eq!(parsed_formula.formula.number_of_variables, 7);
+    }
+
+    #[test]
+    fn parse_dimacs_formula_from_file_should_fail_with_the_path_of_a_missing_file() {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = tmp_dir.path().join("missing.cnf");
+
+        let error = from_file(
+            &dimacs_file_path,
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect_err("The file does not exist");
+
+        assert_eq!(error.path, dimacs_file_path);
+        assert!(matches!(error.kind, ParseErrorKind::Io(_)));
+    }
+
+    #[test]
+    fn parse_should_locate_tokens_that_are_not_integers() {
+        let error = from_reader(
+            "p cnf 3 2\n1 2 0\n-3  x2 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect_err("The formula contains a non integer token");
+
+        assert!(matches!(
+            error,
+            ParseErrorKind::NonIntegerToken { line: 3, column: 5, token } if token == "x2"
+        ));
+    }
+
+    #[test]
+    fn parse_should_report_literals_that_overflow() {
+        let error = from_reader(
+            "p cnf 3 1\n1 99999999999 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect_err("The formula contains a literal that overflows");
+
+        assert!(matches!(
+            error,
+            ParseErrorKind::LiteralOverflow { line: 2, column: 3, token } if token == "99999999999"
+        ));
+    }
+
+    #[test]
+    fn parse_should_reject_malformed_headers() {
+        let error = from_reader(
+            "p dnf 3 1\n1 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect_err("The formula header is malformed");
+
+        assert!(matches!(error, ParseErrorKind::BadHeader { line: 1, .. }));
+    }
+
+    #[test]
+    fn parse_should_reject_clauses_that_are_not_terminated_by_0() {
+        let error = from_reader(
+            "p cnf 3 2\n1 2 0\n-3 2\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect_err("The last clause is not terminated");
+
+        assert!(matches!(
+            error,
+            ParseErrorKind::UnterminatedClause { line: 3 }
+        ));
+    }
+
+    #[test]
+    fn parse_should_report_the_line_an_unterminated_clause_starts_on() {
+        let error = from_reader(
+            "p cnf 3 2\n1 2 0 -3\n2\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect_err("The last clause is not terminated");
+
+        assert!(matches!(
+            error,
+            ParseErrorKind::UnterminatedClause { line: 2 }
+        ));
+    }
+
+    #[test]
+    fn parse_should_accept_an_unterminated_last_clause_when_lenient() {
+        let parsed_formula = from_reader(
+            "p cnf 3 2\n1 2 0\n-3 2\n".as_bytes(),
+            Strictness::Lenient,
+            Simplification::Simplify,
+        )
+        .expect("Lenient parsing accepts an unterminated last clause");
+
+        assert_eq!(
+            parsed_formula.formula.clauses().last(),
+            Some(literals(&[(2, false), (1, true)]).as_slice())
+        );
+        assert!(parsed_formula.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn parse_should_split_a_line_into_several_clauses_on_0() {
+        let parsed_formula = from_reader(
+            "p cnf 4 2\n1 2 0 -3 4 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The formula is well formed");
+
+        assert_eq!(
+            clauses(&parsed_formula.formula),
+            vec![
+                literals(&[(0, true), (1, true)]),
+                literals(&[(2, false), (3, true)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_should_join_a_clause_wrapped_over_several_lines() {
+        let parsed_formula = from_reader(
+            "p cnf 4 1\n1 2\nc a comment in the middle of a clause\n-3\n 4 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The formula is well formed");
+
+        assert_eq!(
+            clauses(&parsed_formula.formula),
+            vec![literals(&[(0, true), (1, true), (2, false), (3, true)])]
+        );
+    }
+
+    #[test]
+    fn parse_should_reject_lines_that_are_not_utf8() {
+        let error = from_reader(
+            &b"p cnf 1 1\nc \xff\n1 0\n"[..],
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect_err("The formula contains invalid UTF-8");
+
+        assert!(matches!(error, ParseErrorKind::InvalidUtf8 { line: 2 }));
+    }
+
+    const COMPRESSED_FORMULA: &str = "p cnf 3 2\n1 -2 0\n2 3 0\n";
+
+    fn assert_compressed_formula_is_parsed(dimacs_file_path: &Path) {
+        let parsed_formula = from_file(
+            dimacs_file_path,
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The formula is well formed");
+
+        assert_eq!(
+            parsed_formula,
+            from_reader(
+                COMPRESSED_FORMULA.as_bytes(),
+                Strictness::Strict,
+                Simplification::Simplify
+            )
+            .expect("The formula is well formed")
+        );
+    }
+
+    #[test]
+    fn parse_dimacs_formula_from_a_gzip_file() {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = tmp_dir.path().join("dimacs.cnf.gz");
+        let dimacs_file =
+            File::create(&dimacs_file_path).expect("Temporary test file could not be created");
+        let mut encoder =
+            flate2::write::GzEncoder::new(dimacs_file, flate2::Compression::default());
+        write!(encoder, "{COMPRESSED_FORMULA}").expect("Could not write content in dimacs file");
+        encoder.finish().expect("Could not finish the gzip stream");
+
+        assert_compressed_formula_is_parsed(&dimacs_file_path);
+    }
+
+    #[test]
+    fn parse_dimacs_formula_from_an_xz_file() {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = tmp_dir.path().join("dimacs.cnf.xz");
+        let dimacs_file =
+            File::create(&dimacs_file_path).expect("Temporary test file could not be created");
+        let mut encoder = liblzma::write::XzEncoder::new(dimacs_file, 6);
+        write!(encoder, "{COMPRESSED_FORMULA}").expect("Could not write content in dimacs file");
+        encoder.finish().expect("Could not finish the xz stream");
+
+        assert_compressed_formula_is_parsed(&dimacs_file_path);
+    }
+
+    #[test]
+    fn parse_dimacs_formula_from_a_bzip2_file() {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = tmp_dir.path().join("dimacs.cnf.bz2");
+        let dimacs_file =
+            File::create(&dimacs_file_path).expect("Temporary test file could not be created");
+        let mut encoder = bzip2::write::BzEncoder::new(dimacs_file, bzip2::Compression::default());
+        write!(encoder, "{COMPRESSED_FORMULA}").expect("Could not write content in dimacs file");
+        encoder.finish().expect("Could not finish the bzip2 stream");
+
+        assert_compressed_formula_is_parsed(&dimacs_file_path);
+    }
+
+    #[test]
+    fn parse_dimacs_formula_shorter_than_the_longest_magic_bytes() {
+        let tmp_dir = tempdir().expect("Could not create test temporary directory");
+        let dimacs_file_path = write_dimacs_file(&tmp_dir, "1 0\n");
+
+        let parsed_formula = from_file(
+            &dimacs_file_path,
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The formula is well formed");
+
+        assert_eq!(parsed_formula.formula.number_of_clauses(), 1);
+    }
+
+    #[test]
+    fn parse_should_drop_tautological_clauses() {
+        let parsed_formula = from_reader(
+            "p cnf 3 3\n1 -1 3 0\n2 0\n-3 2 3 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The formula is well formed");
+
+        assert_eq!(
+            clauses(&parsed_formula.formula),
+            vec![literals(&[(1, true)])]
+        );
+        assert_eq!(parsed_formula.statistics.tautological_clauses, 2);
+        assert_eq!(parsed_formula.clause_indices, vec![1]);
+        assert!(parsed_formula.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn parse_should_keep_a_single_occurrence_of_duplicate_literals() {
+        let parsed_formula = from_reader(
+            "p cnf 3 1\n1 -2 1 3 -2 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Simplify,
+        )
+        .expect("The formula is well formed");
+
+        assert_eq!(
+            clauses(&parsed_formula.formula),
+            vec![literals(&[(0, true), (1, false), (2, true)])]
+        );
+        assert_eq!(parsed_formula.statistics.duplicate_literals, 2);
+    }
+
+    #[test]
+    fn parse_should_keep_the_clauses_as_written_when_asked_to() {
+        let parsed_formula = from_reader(
+            "p cnf 3 2\n1 -1 3 0\n1 -2 1 0\n".as_bytes(),
+            Strictness::Strict,
+            Simplification::Keep,
+        )
+        .expect("The formula is well formed");
 
-        assert_eq!(formula.number_of_literals, expected_number_of_literals);
+        assert_eq!(
+            clauses(&parsed_formula.formula),
+            vec![
+                literals(&[(0, true), (0, false), (2, true)]),
+                literals(&[(0, true), (1, false), (0, true)])
+            ]
+        );
+        assert_eq!(parsed_formula.statistics, ParseStatistics::default());
+        assert_eq!(parsed_formula.clause_indices, vec![0, 1]);
     }
 }
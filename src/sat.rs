@@ -1,28 +1,227 @@
-use std::collections::HashMap;
+use std::fmt;
 
+use rand::{Rng, RngExt};
+
+/// A variable and the value that satisfies it, packed in a single integer
+/// as twice the variable index, plus one when the literal is negated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal(u32);
+
+impl Literal {
+    /// Creates the literal of the 0-based `variable` that is satisfied when
+    /// the variable takes `value`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variable index does not fit within 31 bits
+    #[must_use]
+    pub fn new(variable: usize, value: bool) -> Self {
+        let variable = u32::try_from(variable).expect("Variable indices should fit within 31 bits");
+        Literal(variable << 1 | u32::from(!value))
+    }
+
+    #[must_use]
+    pub fn variable(self) -> usize {
+        (self.0 >> 1) as usize
+    }
+
+    /// The value of the variable that satisfies this literal
+    #[must_use]
+    pub fn value(self) -> bool {
+        self.0 & 1 == 0
+    }
+
+    /// A dense index of the literal, distinct for the two literals of each variable
+    #[must_use]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Formats the literal as in the dimacs format, its 1-based variable negated when the
+/// literal is satisfied by false
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.value() {
+            write!(f, "{}", self.variable() + 1)
+        } else {
+            write!(f, "-{}", self.variable() + 1)
+        }
+    }
+}
+
+/// A CNF formula whose clauses are stored one after the other in a single
+/// literal arena, each clause being delimited by its offset in the arena
 #[derive(Debug, PartialEq)]
 pub struct Formula {
-    pub clauses: Vec<Clause>,
-    pub number_of_literals: usize,
+    literals: Vec<Literal>,
+    clause_offsets: Vec<usize>,
+    pub number_of_variables: usize,
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Clause {
-    pub literals: HashMap<usize, bool>,
+impl Formula {
+    #[must_use]
+    pub fn new(number_of_variables: usize) -> Self {
+        Formula {
+            literals: Vec::new(),
+            clause_offsets: vec![0],
+            number_of_variables,
+        }
+    }
+
+    pub fn push_clause(&mut self, clause: &[Literal]) {
+        self.literals.extend_from_slice(clause);
+        self.clause_offsets.push(self.literals.len());
+    }
+
+    #[must_use]
+    pub fn number_of_clauses(&self) -> usize {
+        self.clause_offsets.len() - 1
+    }
+
+    #[must_use]
+    pub fn clause(&self, clause_index: usize) -> &[Literal] {
+        &self.literals[self.clause_offsets[clause_index]..self.clause_offsets[clause_index + 1]]
+    }
+
+    #[must_use]
+    pub fn clauses(&self) -> impl ExactSizeIterator<Item = &[Literal]> {
+        self.clause_offsets
+            .windows(2)
+            .map(|bounds| &self.literals[bounds[0]..bounds[1]])
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+const BITS_PER_WORD: usize = u64::BITS as usize;
+
+/// An assignment of every variable of a formula, packed one bit per variable.
+/// The bits of the last word past the number of variables are always 0
+#[derive(Clone, PartialEq)]
 pub struct Solution {
-    pub literals: Vec<bool>,
+    words: Vec<u64>,
+    number_of_variables: usize,
 }
 
 impl Solution {
+    /// Creates a solution assigning false to all `number_of_variables` variables
+    #[must_use]
+    pub fn new(number_of_variables: usize) -> Self {
+        Solution {
+            words: vec![0; number_of_variables.div_ceil(BITS_PER_WORD)],
+            number_of_variables,
+        }
+    }
+
+    /// Creates a solution where each variable is true or false with the same probability
+    pub fn random<R: Rng + ?Sized>(number_of_variables: usize, rng: &mut R) -> Self {
+        let words = (0..number_of_variables.div_ceil(BITS_PER_WORD))
+            .map(|_| rng.random())
+            .collect();
+        Solution::from_words(words, number_of_variables)
+    }
+
+    /// Creates a solution from its packed words, the bit `i % 64` of the word `i / 64`
+    /// being the value of the variable `i`. Bits past `number_of_variables` are ignored
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are not exactly enough words to hold `number_of_variables` bits
+    #[must_use]
+    pub fn from_words(mut words: Vec<u64>, number_of_variables: usize) -> Self {
+        assert_eq!(
+            words.len(),
+            number_of_variables.div_ceil(BITS_PER_WORD),
+            "The number of words does not match the number of variables"
+        );
+        let used_bits_in_last_word = number_of_variables % BITS_PER_WORD;
+        if used_bits_in_last_word != 0
+            && let Some(last_word) = words.last_mut()
+        {
+            *last_word &= (1 << used_bits_in_last_word) - 1;
+        }
+        Solution {
+            words,
+            number_of_variables,
+        }
+    }
+
+    #[must_use]
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.number_of_variables
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.number_of_variables == 0
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the solution has no value for `variable`
+    #[must_use]
+    pub fn get(&self, variable: usize) -> bool {
+        assert!(
+            variable < self.number_of_variables,
+            "The solution does not contain a value for all variables"
+        );
+        self.words[variable / BITS_PER_WORD] >> (variable % BITS_PER_WORD) & 1 == 1
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the solution has no value for `variable`
+    pub fn set(&mut self, variable: usize, value: bool) {
+        if self.get(variable) != value {
+            self.flip(variable);
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the solution has no value for `variable`
+    pub fn flip(&mut self, variable: usize) {
+        assert!(
+            variable < self.number_of_variables,
+            "The solution does not contain a value for all variables"
+        );
+        self.words[variable / BITS_PER_WORD] ^= 1 << (variable % BITS_PER_WORD);
+    }
+
+    /// The number of variables on which both solutions differ
+    #[must_use]
+    pub fn hamming_distance(&self, other: &Solution) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(word, other_word)| (word ^ other_word).count_ones() as usize)
+            .sum()
+    }
+
+    /// The values of the variables, in order
+    #[must_use]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = bool> + '_ {
+        (0..self.number_of_variables).map(|variable| self.get(variable))
+    }
+
     /// This returns the ratio of clauses that the solution
-    /// satisfies
+    /// satisfies, 1 for a formula without clauses
+    ///
+    /// # Panics
+    ///
+    /// Panics if the formula has more clauses than `u32::MAX`,
+    /// or if the solution is shorter than the number of variables of the formula
+    #[must_use]
     pub fn evaluate(&self, formula: &Formula) -> f64 {
+        if formula.number_of_clauses() == 0 {
+            return 1.;
+        }
         let num_satisfied_clauses = formula
-            .clauses
-            .iter()
+            .clauses()
             .filter(|clause| self.satisfies_clause(clause))
             .count();
 
@@ -30,24 +229,124 @@ impl Solution {
             u32::try_from(num_satisfied_clauses)
                 .expect("The number of clauses should be less than the maximum value of u32"),
         ) / f64::from(
-            u32::try_from(formula.clauses.len())
+            u32::try_from(formula.number_of_clauses())
                 .expect("The number of clauses should be less than the maximum value of u32"),
         )
     }
 
     /// Returns true if at least one of the literals in the clause
-    /// has the same value as its matching literal in the solution.
+    /// has the same value as its matching variable in the solution.
     /// Returns false otherwise
-    pub fn satisfies_clause(&self, clause: &Clause) -> bool {
-        for (literal_number, literal_value) in &clause.literals {
-            let associated_solution_value = self
-                .literals
-                .get(*literal_number)
-                .expect("The solution does not contain a value for all literals");
-            if literal_value == associated_solution_value {
-                return true;
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clause contains a variable the solution has no value for
+    #[must_use]
+    pub fn satisfies_clause(&self, clause: &[Literal]) -> bool {
+        clause
+            .iter()
+            .any(|literal| self.get(literal.variable()) == literal.value())
+    }
+}
+
+impl fmt::Debug for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Solution")
+            .field("literals", &self.iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl FromIterator<bool> for Solution {
+    fn from_iter<I: IntoIterator<Item = bool>>(values: I) -> Self {
+        let mut words = Vec::new();
+        let mut number_of_variables = 0;
+        for value in values {
+            if number_of_variables % BITS_PER_WORD == 0 {
+                words.push(0);
+            }
+            if value && let Some(last_word) = words.last_mut() {
+                *last_word |= 1 << (number_of_variables % BITS_PER_WORD);
             }
+            number_of_variables += 1;
+        }
+        Solution {
+            words,
+            number_of_variables,
         }
-        false
+    }
+}
+
+impl<const N: usize> From<[bool; N]> for Solution {
+    fn from(values: [bool; N]) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn literals_should_display_in_the_dimacs_format() {
+        assert_eq!(Literal::new(0, true).to_string(), "1");
+        assert_eq!(Literal::new(41, false).to_string(), "-42");
+    }
+
+    #[test]
+    fn solution_from_values_should_keep_the_value_of_each_variable() {
+        let values: Vec<bool> = (0..130).map(|variable| variable % 3 == 0).collect();
+
+        let solution: Solution = values.iter().copied().collect();
+
+        assert_eq!(solution.len(), 130);
+        assert_eq!(solution.iter().collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn flip_should_only_change_the_flipped_variable() {
+        let mut solution = Solution::new(70);
+
+        solution.flip(65);
+
+        assert!(solution.get(65));
+        assert_eq!(solution.iter().filter(|value| *value).count(), 1);
+        solution.flip(65);
+        assert_eq!(solution, Solution::new(70));
+    }
+
+    #[test]
+    fn set_should_assign_the_given_value() {
+        let mut solution = Solution::from([true, false, true]);
+
+        solution.set(0, false);
+        solution.set(1, true);
+        solution.set(2, true);
+
+        assert_eq!(solution, Solution::from([false, true, true]));
+    }
+
+    #[test]
+    fn hamming_distance_should_count_the_variables_with_different_values() {
+        let first_solution = Solution::from([true, false, true, true]);
+        let second_solution = Solution::from([false, false, true, false]);
+
+        assert_eq!(first_solution.hamming_distance(&second_solution), 2);
+    }
+
+    #[test]
+    fn from_words_should_clear_the_bits_past_the_number_of_variables() {
+        let solution = Solution::from_words(vec![u64::MAX, u64::MAX], 66);
+
+        assert_eq!(solution, Solution::from_words(vec![u64::MAX, 0b11], 66));
+        assert_eq!(solution.hamming_distance(&Solution::new(66)), 66);
+    }
+
+    #[test]
+    #[should_panic(expected = "The solution does not contain a value for all variables")]
+    fn get_should_panic_past_the_number_of_variables() {
+        let solution = Solution::new(3);
+
+        let _ = solution.get(3);
     }
 }
@@ -0,0 +1,131 @@
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use bzip2::write::BzEncoder;
+use flate2::write::GzEncoder;
+use liblzma::write::XzEncoder;
+
+use crate::sat::Formula;
+
+/// The compression level of xz output, the default of the xz tool
+const XZ_PRESET: u32 = 6;
+
+/// Writes the formula in the dimacs format to `file_path`, or on the standard output when
+/// the path is `-`. The formula is compressed with gzip, xz or bzip2 when the path ends
+/// with .gz, .xz or .bz2. Each comment is written on a `c` line before the header
+///
+/// # Errors
+///
+/// Returns any error met while creating or writing the file
+pub fn to_file(file_path: &Path, formula: &Formula, comments: &[String]) -> io::Result<()> {
+    if file_path == Path::new("-") {
+        let mut writer = BufWriter::new(io::stdout().lock());
+        to_writer(&mut writer, formula, comments)?;
+        return writer.flush();
+    }
+
+    let writer = BufWriter::new(File::create(file_path)?);
+    let mut writer = match file_path.extension().and_then(OsStr::to_str) {
+        Some("gz") => {
+            let mut encoder = GzEncoder::new(writer, flate2::Compression::default());
+            to_writer(&mut encoder, formula, comments)?;
+            encoder.finish()?
+        }
+        Some("xz") => {
+            let mut encoder = XzEncoder::new(writer, XZ_PRESET);
+            to_writer(&mut encoder, formula, comments)?;
+            encoder.finish()?
+        }
+        Some("bz2") => {
+            let mut encoder = BzEncoder::new(writer, bzip2::Compression::default());
+            to_writer(&mut encoder, formula, comments)?;
+            encoder.finish()?
+        }
+        _ => {
+            let mut writer = writer;
+            to_writer(&mut writer, formula, comments)?;
+            writer
+        }
+    };
+    writer.flush()
+}
+
+/// Writes the comments, the `p cnf` header and a line per clause, each ending with a 0
+///
+/// # Errors
+///
+/// Returns any error of the writer
+pub fn to_writer(
+    writer: &mut impl Write,
+    formula: &Formula,
+    comments: &[String],
+) -> io::Result<()> {
+    for comment in comments {
+        writeln!(writer, "c {comment}")?;
+    }
+    writeln!(
+        writer,
+        "p cnf {} {}",
+        formula.number_of_variables,
+        formula.number_of_clauses()
+    )?;
+    for clause in formula.clauses() {
+        for literal in clause {
+            write!(writer, "{literal} ")?;
+        }
+        writeln!(writer, "0")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_dimacs::{self, Simplification, Strictness};
+    use crate::sat::Literal;
+
+    fn formula() -> Formula {
+        let mut formula = Formula::new(4);
+        formula.push_clause(&[Literal::new(0, true), Literal::new(3, false)]);
+        formula.push_clause(&[]);
+        formula.push_clause(&[Literal::new(1, false)]);
+        formula
+    }
+
+    #[test]
+    fn to_writer_should_write_comments_header_and_clauses() {
+        let mut output = Vec::new();
+
+        to_writer(&mut output, &formula(), &[String::from("a comment")])
+            .expect("Writing to a vector should not fail");
+
+        assert_eq!(
+            String::from_utf8(output).expect("The formula should be valid utf-8"),
+            "c a comment\np cnf 4 3\n1 -4 0\n0\n-2 0\n"
+        );
+    }
+
+    #[test]
+    fn to_file_should_write_formulas_the_parser_reads_back() {
+        let directory = tempfile::tempdir().expect("The temporary directory should be created");
+
+        for file_name in [
+            "formula.cnf",
+            "formula.cnf.gz",
+            "formula.cnf.xz",
+            "formula.cnf.bz2",
+        ] {
+            let file_path = directory.path().join(file_name);
+            to_file(&file_path, &formula(), &[]).expect("The formula should be written");
+
+            let parsed_formula =
+                parse_dimacs::from_file(&file_path, Strictness::Strict, Simplification::Simplify)
+                    .expect("The written formula should be well formed");
+
+            assert_eq!(parsed_formula.formula, formula());
+            assert!(parsed_formula.diagnostics.is_empty());
+        }
+    }
+}